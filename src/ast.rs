@@ -1,3 +1,4 @@
+use lexer::Span;
 use std::fmt::Debug;
 use std::fmt::Error;
 use std::fmt::Formatter;
@@ -6,36 +7,161 @@ use std::fmt::Formatter;
 pub type Name = String;
 pub type Args = Vec<ArgAst>;
 pub type FnArgs = Vec<ExprAst>;
+/// Args of a call that are given by name, in the order they were written
+pub type KwArgs = Vec<(Name, ExprAst)>;
 pub type ListArgs = Vec<Box<ExprAst>>;
-pub type Stmts = (Vec<Box<StmtAst>>, Option<Box<StmtAst>>);
+pub type MapArgs = Vec<(ExprAst, ExprAst)>;
+/// A variant name and the types of its payload
+pub type VariantAst = (Name, Vec<TypesAst>);
+pub type MatchArms = Vec<(PatternAst, Stmts)>;
+pub type Stmts = (Vec<LocatedStmt>, Option<LocatedStmt>);
+/// A statement and where it starts
+pub type LocatedStmt = (Span, Box<StmtAst>);
+pub type TypesAst = Vec<TypeAst>;
+
+/// A single type as written in the source. A `TypesAst` is a union of these
+#[derive(Debug, Clone)]
+pub enum TypeAst {
+    None,
+    /// A built in or user defined type, with where it was written
+    Name(Name, Span),
+    /// A type of an imported module, `module.Type`
+    Qualified(Name, Name, Span),
+    /// `int?`, which is `int | none`
+    Optional(Box<TypeAst>),
+    List(TypesAst),
+    Map(TypesAst, TypesAst),
+    Tuple(Vec<TypesAst>),
+    /// `(int, str) -> bool`, the arg types and the return types
+    Function(Vec<TypesAst>, TypesAst),
+}
+
+/// The names a value is bound to, tuples are destructured into their items
+#[derive(Debug, Clone)]
+pub enum BindingAst {
+    Name(Name),
+    Tuple(Vec<BindingAst>),
+}
+
+impl BindingAst {
+    /// Converts an expression on the left of an `=` into a binding
+    fn from_expr(expr: ExprAst) -> Option<BindingAst> {
+        match expr {
+            ExprAst::Variable(name) => Some(BindingAst::Name(name)),
+            ExprAst::Tuple(items) => {
+                let mut bindings = Vec::new();
+
+                for item in items {
+                    bindings.push(BindingAst::from_expr(*item)?);
+                }
+
+                Some(BindingAst::Tuple(bindings))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns every name that is bound, in order
+    pub fn names(&self) -> Vec<&Name> {
+        match self {
+            BindingAst::Name(name) => vec![name],
+            BindingAst::Tuple(bindings) => bindings.iter().flat_map(|b| b.names()).collect(),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum ModStmtAst {
     FunctionDef {
         name: String,
+        /// The names of the type params of a generic function
+        type_params: Vec<Name>,
         args: Args,
         exprs: Stmts,
-        return_type: Vec<Name>,
+        return_type: TypesAst,
+    },
+    StructDef {
+        name: String,
+        fields: Args,
+    },
+    EnumDef {
+        name: String,
+        variants: Vec<VariantAst>,
+    },
+    /// A module level variable, constants can not be reassigned
+    ///
+    /// Without types the types are inferred from the expression.
+    GlobalDef {
+        name: String,
+        types: Option<TypesAst>,
+        expr: ExprAst,
+        mutable: bool,
+    },
+    /// Imports a module, or only the given names from it
+    Import {
+        module: String,
+        names: Option<Vec<String>>,
     },
 }
 
 impl ModStmtAst {
     pub fn new_fn(
         name: String,
+        type_params: Option<Vec<Name>>,
         args: Args,
-        return_type: Option<Vec<Name>>,
+        return_type: Option<TypesAst>,
         exprs: Stmts,
     ) -> ModStmtAst {
-        let conv_return_type = match return_type {
-            Some(e) => e,
-            None => vec!["none".to_string()],
-        };
-
         ModStmtAst::FunctionDef {
             name,
+            type_params: type_params.unwrap_or_default(),
             args,
             exprs,
-            return_type: conv_return_type,
+            return_type: return_type_or_none(return_type),
+        }
+    }
+}
+
+/// Functions without a declared return type return none
+fn return_type_or_none(return_type: Option<TypesAst>) -> TypesAst {
+    match return_type {
+        Some(e) => e,
+        None => vec![TypeAst::None],
+    }
+}
+
+/// Adds the names used anywhere in the statements to `names`
+pub fn collect_stmts_names(stmts: &Stmts, names: &mut Vec<Name>) {
+    for (_, stmt) in stmts.0.iter().chain(&stmts.1) {
+        stmt.collect_names(names);
+    }
+}
+
+/// Adds the existing names that are reassigned in the statements to `names`
+pub fn collect_stmts_assigned(stmts: &Stmts, names: &mut Vec<Name>) {
+    for (_, stmt) in stmts.0.iter().chain(&stmts.1) {
+        stmt.collect_assigned(names);
+    }
+}
+
+/// A pattern in a match arm
+///
+/// A plain name is either a variant without a payload or a new binding, which
+/// one is decided by the compiler when it resolves the name.
+#[derive(Debug, Clone)]
+pub enum PatternAst {
+    Wildcard,
+    Name(Name),
+    Literal(ExprAst),
+    Variant { name: Name, args: Vec<PatternAst> },
+}
+
+impl PatternAst {
+    pub fn new_name(name: Name) -> PatternAst {
+        if name == "_" {
+            PatternAst::Wildcard
+        } else {
+            PatternAst::Name(name)
         }
     }
 }
@@ -45,28 +171,141 @@ pub enum StmtAst {
     Expr(ExprAst),
     Assignment {
         new: bool,
-        name: String,
+        /// New bindings made with `const` can not be reassigned
+        mutable: bool,
+        binding: BindingAst,
+        expr: ExprAst,
+        types: Option<TypesAst>,
+    },
+    /// `target[index] = expr`, where the target is a variable or a chain of
+    /// fields and indexes of one
+    IndexAssignment {
+        target: ExprAst,
+        index: ExprAst,
+        expr: ExprAst,
+    },
+    /// `target.field = expr`, with the same targets as an index assignment
+    FieldAssignment {
+        target: ExprAst,
+        field: Name,
         expr: ExprAst,
-        types: Option<Vec<Name>>,
     },
+    /// Where the statement is, to report it outside of a loop
+    Break(Span),
+    Continue(Span),
+    Return(Option<ExprAst>, Span),
 }
 
 impl StmtAst {
-    pub fn new_assignment(name: String, types: Vec<Name>, expr: ExprAst) -> StmtAst {
+    /// Adds the names used anywhere in the statement to `names`
+    pub fn collect_names(&self, names: &mut Vec<Name>) {
+        match self {
+            StmtAst::Expr(expr) | StmtAst::Return(Some(expr), _) => expr.collect_names(names),
+            StmtAst::Assignment { binding, expr, .. } => {
+                names.extend(binding.names().into_iter().cloned());
+                expr.collect_names(names);
+            }
+            StmtAst::IndexAssignment {
+                target,
+                index,
+                expr,
+            } => {
+                target.collect_names(names);
+                index.collect_names(names);
+                expr.collect_names(names);
+            }
+            StmtAst::FieldAssignment { target, expr, .. } => {
+                target.collect_names(names);
+                expr.collect_names(names);
+            }
+            StmtAst::Break(_) | StmtAst::Continue(_) | StmtAst::Return(None, _) => {}
+        }
+    }
+
+    /// Adds the existing names that are reassigned anywhere in the statement to `names`
+    ///
+    /// Names defined by the statement are left out, as are the names assigned
+    /// in function literals which can not reassign the names around them.
+    pub fn collect_assigned(&self, names: &mut Vec<Name>) {
+        match self {
+            StmtAst::Expr(expr) | StmtAst::Return(Some(expr), _) => expr.collect_assigned(names),
+            StmtAst::Assignment {
+                new, binding, expr, ..
+            } => {
+                if !new {
+                    names.extend(binding.names().into_iter().cloned());
+                }
+
+                expr.collect_assigned(names);
+            }
+            StmtAst::IndexAssignment {
+                target,
+                index,
+                expr,
+            } => {
+                target.collect_assigned(names);
+                index.collect_assigned(names);
+                expr.collect_assigned(names);
+            }
+            StmtAst::FieldAssignment { target, expr, .. } => {
+                target.collect_assigned(names);
+                expr.collect_assigned(names);
+            }
+            StmtAst::Break(_) | StmtAst::Continue(_) | StmtAst::Return(None, _) => {}
+        }
+    }
+
+    /// Returns true if the statement leaves a value on the stack
+    pub fn has_value(&self) -> bool {
+        match self {
+            StmtAst::Expr(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Without types the types of the binding are inferred from the expression
+    pub fn new_assignment(
+        binding: BindingAst,
+        types: Option<TypesAst>,
+        expr: ExprAst,
+        mutable: bool,
+    ) -> StmtAst {
         StmtAst::Assignment {
             new: true,
-            name: name,
+            mutable: mutable,
+            binding: binding,
             expr: expr,
-            types: Some(types),
+            types: types,
         }
     }
 
-    pub fn new_reassignment(name: String, expr: ExprAst) -> StmtAst {
-        StmtAst::Assignment {
-            new: false,
-            name: name,
-            expr: expr,
-            types: None,
+    /// Creates an assignment to existing names or to an index of one
+    ///
+    /// Returns None if the target can not be assigned to
+    pub fn new_reassignment(target: ExprAst, expr: ExprAst) -> Option<StmtAst> {
+        match target {
+            ExprAst::Index { expr: target, index } if target.is_place() => {
+                Some(StmtAst::IndexAssignment {
+                    target: *target,
+                    index: *index,
+                    expr: expr,
+                })
+            }
+            ExprAst::Field { expr: target, field } if target.is_place() => {
+                Some(StmtAst::FieldAssignment {
+                    target: *target,
+                    field: field,
+                    expr: expr,
+                })
+            }
+            ExprAst::Index { .. } | ExprAst::Field { .. } => None,
+            target => Some(StmtAst::Assignment {
+                new: false,
+                mutable: true,
+                binding: BindingAst::from_expr(target)?,
+                expr: expr,
+                types: None,
+            }),
         }
     }
 }
@@ -74,14 +313,16 @@ impl StmtAst {
 #[derive(Debug, Clone)]
 pub struct ArgAst {
     name: Name,
-    arg_types: Vec<Name>,
+    arg_types: TypesAst,
+    default: Option<ExprAst>,
 }
 
 impl ArgAst {
-    pub fn new(name: Name, arg_type: Vec<Name>) -> ArgAst {
+    pub fn new(name: Name, arg_type: TypesAst, default: Option<ExprAst>) -> ArgAst {
         ArgAst {
             name: name,
             arg_types: arg_type,
+            default: default,
         }
     }
 
@@ -89,15 +330,30 @@ impl ArgAst {
         &self.name
     }
 
-    pub fn arg_types(&self) -> &Vec<Name> {
+    pub fn arg_types(&self) -> &TypesAst {
         &self.arg_types
     }
+
+    /// The value used when a call leaves the arg out
+    pub fn default(&self) -> Option<&ExprAst> {
+        self.default.as_ref()
+    }
+}
+
+/// An arg of a call, `f(1, b: 2)` has a positional and a keyword arg
+#[derive(Debug, Clone)]
+pub enum CallArgAst {
+    Positional(ExprAst),
+    Keyword(Name, ExprAst),
 }
 
 #[derive(Copy, Clone)]
 pub enum BinOpAst {
     Mul,
     Div,
+    FloorDiv,
+    Mod,
+    Pow,
     Add,
     Sub,
     EqEq,
@@ -106,6 +362,9 @@ pub enum BinOpAst {
     LessThanEq,
     GreaterThan,
     GreaterThanEq,
+    And,
+    Or,
+    In,
 }
 
 impl Debug for BinOpAst {
@@ -114,6 +373,9 @@ impl Debug for BinOpAst {
         match *self {
             Mul => write!(fmt, "*"),
             Div => write!(fmt, "/"),
+            FloorDiv => write!(fmt, "//"),
+            Mod => write!(fmt, "%"),
+            Pow => write!(fmt, "**"),
             Add => write!(fmt, "+"),
             Sub => write!(fmt, "-"),
             EqEq => write!(fmt, "=="),
@@ -122,6 +384,27 @@ impl Debug for BinOpAst {
             LessThanEq => write!(fmt, "<="),
             GreaterThan => write!(fmt, ">"),
             GreaterThanEq => write!(fmt, ">="),
+            And => write!(fmt, "and"),
+            Or => write!(fmt, "or"),
+            In => write!(fmt, "in"),
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub enum UnaryOpAst {
+    Not,
+    Neg,
+    Plus,
+}
+
+impl Debug for UnaryOpAst {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
+        use self::UnaryOpAst::*;
+        match *self {
+            Not => write!(fmt, "not"),
+            Neg => write!(fmt, "-"),
+            Plus => write!(fmt, "+"),
         }
     }
 }
@@ -131,13 +414,36 @@ pub enum ExprAst {
     FunctionCall {
         name: Name,
         args: FnArgs,
+        kwargs: KwArgs,
     },
-    Integer(i64),
+    // Unsigned so that `-9223372036854775808` can be folded by the compiler,
+    // which reports literals that are out of range at their location
+    Integer(u64, Span),
+    Float(f64),
     Op(Box<ExprAst>, BinOpAst, Box<ExprAst>),
+    UnaryOp(UnaryOpAst, Box<ExprAst>),
     Variable(String),
     Bool(bool),
     Str(String),
+    None,
+    /// `f"a {b}"`, the text is kept as `Str` parts between the expressions
+    Format(FnArgs),
     List(ListArgs),
+    Tuple(ListArgs),
+    Map(MapArgs),
+    Index {
+        expr: Box<ExprAst>,
+        index: Box<ExprAst>,
+    },
+    Field {
+        expr: Box<ExprAst>,
+        field: Name,
+    },
+    Slice {
+        expr: Box<ExprAst>,
+        start: Option<Box<ExprAst>>,
+        end: Option<Box<ExprAst>>,
+    },
     If {
         cmp_expr: Box<ExprAst>,
         exprs: Stmts,
@@ -148,17 +454,271 @@ pub enum ExprAst {
         exprs: Stmts,
     },
     For {
-        name: String,
+        binding: BindingAst,
         range: RangeAst,
         exprs: Stmts,
     },
+    Match {
+        expr: Box<ExprAst>,
+        arms: MatchArms,
+    },
+    /// Calls the result of an expression, calls of a name are a `FunctionCall`
+    Call {
+        expr: Box<ExprAst>,
+        args: FnArgs,
+        kwargs: KwArgs,
+    },
+    /// An anonymous function, it captures the locals it uses when it is created
+    Function {
+        args: Args,
+        return_type: TypesAst,
+        exprs: Stmts,
+    },
 }
 
 impl ExprAst {
+    /// Returns true for a variable and for fields and indexes of one, which can be assigned to
+    pub fn is_place(&self) -> bool {
+        match self {
+            ExprAst::Variable(_) => true,
+            ExprAst::Index { expr, .. } | ExprAst::Field { expr, .. } => expr.is_place(),
+            _ => false,
+        }
+    }
+
+    /// Adds the names used anywhere in the expression to `names`
+    ///
+    /// Names that are bound inside the expression are included as well.
+    pub fn collect_names(&self, names: &mut Vec<Name>) {
+        match self {
+            ExprAst::Variable(name) => names.push(name.clone()),
+            ExprAst::FunctionCall { name, args, kwargs } => {
+                names.push(name.clone());
+
+                for arg in args {
+                    arg.collect_names(names);
+                }
+
+                for (_, arg) in kwargs {
+                    arg.collect_names(names);
+                }
+            }
+            ExprAst::Integer(..)
+            | ExprAst::Float(_)
+            | ExprAst::Bool(_)
+            | ExprAst::Str(_)
+            | ExprAst::None => {}
+            ExprAst::Op(left, _, right) => {
+                left.collect_names(names);
+                right.collect_names(names);
+            }
+            ExprAst::UnaryOp(_, expr) | ExprAst::Field { expr, .. } => expr.collect_names(names),
+            ExprAst::Format(parts) => {
+                for part in parts {
+                    part.collect_names(names);
+                }
+            }
+            ExprAst::List(exprs) | ExprAst::Tuple(exprs) => {
+                for expr in exprs {
+                    expr.collect_names(names);
+                }
+            }
+            ExprAst::Map(entries) => {
+                for (key, value) in entries {
+                    key.collect_names(names);
+                    value.collect_names(names);
+                }
+            }
+            ExprAst::Index { expr, index } => {
+                expr.collect_names(names);
+                index.collect_names(names);
+            }
+            ExprAst::Slice { expr, start, end } => {
+                expr.collect_names(names);
+
+                for bound in start.iter().chain(end) {
+                    bound.collect_names(names);
+                }
+            }
+            ExprAst::If {
+                cmp_expr,
+                exprs,
+                else_exprs,
+            } => {
+                cmp_expr.collect_names(names);
+                collect_stmts_names(exprs, names);
+
+                if let Some(else_exprs) = else_exprs {
+                    collect_stmts_names(else_exprs, names);
+                }
+            }
+            ExprAst::While { cmp_expr, exprs } => {
+                cmp_expr.collect_names(names);
+                collect_stmts_names(exprs, names);
+            }
+            ExprAst::For {
+                binding,
+                range,
+                exprs,
+            } => {
+                names.extend(binding.names().into_iter().cloned());
+
+                match range {
+                    RangeAst::Range { start, end, step } => {
+                        start.collect_names(names);
+                        end.collect_names(names);
+
+                        if let Some(step) = step {
+                            step.collect_names(names);
+                        }
+                    }
+                    RangeAst::Iter(expr) => expr.collect_names(names),
+                }
+
+                collect_stmts_names(exprs, names);
+            }
+            ExprAst::Match { expr, arms } => {
+                expr.collect_names(names);
+
+                for (_, exprs) in arms {
+                    collect_stmts_names(exprs, names);
+                }
+            }
+            ExprAst::Call { expr, args, kwargs } => {
+                expr.collect_names(names);
+
+                for arg in args {
+                    arg.collect_names(names);
+                }
+
+                for (_, arg) in kwargs {
+                    arg.collect_names(names);
+                }
+            }
+            ExprAst::Function { exprs, .. } => collect_stmts_names(exprs, names),
+        }
+    }
+
+    /// Adds the existing names that are reassigned anywhere in the expression to `names`
+    pub fn collect_assigned(&self, names: &mut Vec<Name>) {
+        let mut exprs: Vec<&ExprAst> = Vec::new();
+        let mut blocks: Vec<&Stmts> = Vec::new();
+
+        match self {
+            ExprAst::FunctionCall { args, kwargs, .. } => {
+                exprs.extend(args);
+                exprs.extend(kwargs.iter().map(|(_, arg)| arg));
+            }
+            ExprAst::Call { expr, args, kwargs } => {
+                exprs.push(expr);
+                exprs.extend(args);
+                exprs.extend(kwargs.iter().map(|(_, arg)| arg));
+            }
+            ExprAst::Integer(..)
+            | ExprAst::Float(_)
+            | ExprAst::Bool(_)
+            | ExprAst::Str(_)
+            | ExprAst::None
+            | ExprAst::Variable(_)
+            | ExprAst::Function { .. } => {}
+            ExprAst::Op(left, _, right) => {
+                exprs.push(left);
+                exprs.push(right);
+            }
+            ExprAst::UnaryOp(_, expr) | ExprAst::Field { expr, .. } => exprs.push(expr),
+            ExprAst::Format(parts) => exprs.extend(parts),
+            ExprAst::List(items) | ExprAst::Tuple(items) => {
+                exprs.extend(items.iter().map(|item| &**item))
+            }
+            ExprAst::Map(entries) => {
+                for (key, value) in entries {
+                    exprs.push(key);
+                    exprs.push(value);
+                }
+            }
+            ExprAst::Index { expr, index } => {
+                exprs.push(expr);
+                exprs.push(index);
+            }
+            ExprAst::Slice { expr, start, end } => {
+                exprs.push(expr);
+                exprs.extend(start.iter().chain(end).map(|bound| &**bound));
+            }
+            ExprAst::If {
+                cmp_expr,
+                exprs: then_exprs,
+                else_exprs,
+            } => {
+                exprs.push(cmp_expr);
+                blocks.push(then_exprs);
+                blocks.extend(else_exprs);
+            }
+            ExprAst::While {
+                cmp_expr,
+                exprs: body,
+            } => {
+                exprs.push(cmp_expr);
+                blocks.push(body);
+            }
+            ExprAst::For {
+                range, exprs: body, ..
+            } => {
+                match range {
+                    RangeAst::Range { start, end, step } => {
+                        exprs.push(start);
+                        exprs.push(end);
+                        exprs.extend(step.iter().map(|step| &**step));
+                    }
+                    RangeAst::Iter(expr) => exprs.push(expr),
+                }
+
+                blocks.push(body);
+            }
+            ExprAst::Match { expr, arms } => {
+                exprs.push(expr);
+                blocks.extend(arms.iter().map(|(_, body)| body));
+            }
+        }
+
+        for expr in exprs {
+            expr.collect_assigned(names);
+        }
+
+        for block in blocks {
+            collect_stmts_assigned(block, names);
+        }
+    }
+
     pub fn new_op(left: ExprAst, op: BinOpAst, right: ExprAst) -> ExprAst {
         ExprAst::Op(Box::new(left), op, Box::new(right))
     }
 
+    pub fn new_unary(op: UnaryOpAst, expr: ExprAst) -> ExprAst {
+        ExprAst::UnaryOp(op, Box::new(expr))
+    }
+
+    pub fn new_index(expr: ExprAst, index: ExprAst) -> ExprAst {
+        ExprAst::Index {
+            expr: Box::new(expr),
+            index: Box::new(index),
+        }
+    }
+
+    pub fn new_field(expr: ExprAst, field: Name) -> ExprAst {
+        ExprAst::Field {
+            expr: Box::new(expr),
+            field: field,
+        }
+    }
+
+    pub fn new_slice(expr: ExprAst, start: Option<ExprAst>, end: Option<ExprAst>) -> ExprAst {
+        ExprAst::Slice {
+            expr: Box::new(expr),
+            start: start.map(Box::new),
+            end: end.map(Box::new),
+        }
+    }
+
     pub fn new_if(cmp_expr: ExprAst, exprs: Stmts, else_exprs: Option<Stmts>) -> ExprAst {
         ExprAst::If {
             cmp_expr: Box::new(cmp_expr),
@@ -174,43 +734,101 @@ impl ExprAst {
         }
     }
 
-    pub fn new_for(name: String, range: RangeAst, exprs: Stmts) -> ExprAst {
+    pub fn new_for(binding: BindingAst, range: RangeAst, exprs: Stmts) -> ExprAst {
         ExprAst::For {
-            name: name,
+            binding: binding,
             range: range,
             exprs: exprs,
         }
     }
 
+    pub fn new_match(expr: ExprAst, arms: MatchArms) -> ExprAst {
+        ExprAst::Match {
+            expr: Box::new(expr),
+            arms: arms,
+        }
+    }
+
+    /// Calls of a name are kept as a `FunctionCall` so they can be resolved directly
+    ///
+    /// Returns `None` if an arg given by position follows one given by name.
+    pub fn new_call(expr: ExprAst, call_args: Vec<CallArgAst>) -> Option<ExprAst> {
+        let mut args = Vec::new();
+        let mut kwargs = Vec::new();
+
+        for call_arg in call_args {
+            match call_arg {
+                CallArgAst::Positional(arg) => {
+                    if !kwargs.is_empty() {
+                        return None;
+                    }
+
+                    args.push(arg);
+                }
+                CallArgAst::Keyword(name, arg) => kwargs.push((name, arg)),
+            }
+        }
+
+        Some(match expr {
+            ExprAst::Variable(name) => ExprAst::FunctionCall { name, args, kwargs },
+            expr => ExprAst::Call {
+                expr: Box::new(expr),
+                args,
+                kwargs,
+            },
+        })
+    }
+
+    pub fn new_function(args: Args, return_type: Option<TypesAst>, exprs: Stmts) -> ExprAst {
+        ExprAst::Function {
+            args,
+            return_type: return_type_or_none(return_type),
+            exprs,
+        }
+    }
+
     pub fn new_list(exprs: FnArgs) -> ExprAst {
         ExprAst::List(
             exprs
                 .into_iter()
-                .map(|x| Box::new(x))
+                .map(Box::new)
+                .collect::<Vec<Box<ExprAst>>>(),
+        )
+    }
+
+    pub fn new_tuple(first: ExprAst, rest: FnArgs) -> ExprAst {
+        ExprAst::Tuple(
+            Some(first)
+                .into_iter()
+                .chain(rest)
+                .map(Box::new)
                 .collect::<Vec<Box<ExprAst>>>(),
         )
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct RangeAst {
-    start: i64,
-    end: i64,
+pub enum RangeAst {
+    /// `start to end step step`, the step defaults to 1 or -1 based on the bounds
+    Range {
+        start: Box<ExprAst>,
+        end: Box<ExprAst>,
+        step: Option<Box<ExprAst>>,
+    },
+    /// Iterates over the items of a list or the characters of a string
+    Iter(Box<ExprAst>),
 }
 
 impl RangeAst {
-    pub fn new(start: i64, end: i64) -> RangeAst {
-        RangeAst {
-            start: start,
-            end: end,
+    pub fn new_range(start: ExprAst, end: ExprAst, step: Option<ExprAst>) -> RangeAst {
+        RangeAst::Range {
+            start: Box::new(start),
+            end: Box::new(end),
+            step: step.map(Box::new),
         }
     }
 
-    pub fn start(&self) -> i64 {
-        self.start
-    }
-
-    pub fn end(&self) -> i64 {
-        self.end
+    pub fn new_iter(expr: ExprAst) -> RangeAst {
+        RangeAst::Iter(Box::new(expr))
     }
 }
@@ -1,96 +1,1988 @@
 use ast::*;
 use errors::GearsError;
 use lexer;
-use module::{Module, ModuleBuilder};
+use module::{Module, ModuleBuilder, Param};
 use parser;
+use resolver::{FileResolver, MemoryResolver, ModuleResolver};
 use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::prelude::*;
 use std::iter::FromIterator;
-use symbol::{SymbolTable, SymbolType, Type, Types};
+use std::path::Path;
+use symbol::{Symbol, SymbolTable, SymbolType};
+use types::{
+    infer_type_params, is_numeric, is_optional, normalize_types, numeric_result, substitute_types,
+    types_assignable, without_none, Substitution, Type, Types,
+};
+use vm;
 
-/// Compile a gears file to a module
+/// Type names that user defined types can not use
+const BUILTIN_TYPES: [&str; 9] = [
+    "none", "int", "float", "bool", "str", "list", "map", "tuple", "function",
+];
+
+/// Compile a gears file to a module, imported modules are found next to the file
 pub fn compile_file(filename: &str) -> Result<Module, GearsError> {
     let mut f = File::open(filename)?;
     let mut contents = String::new();
     f.read_to_string(&mut contents)?;
-    compile_str(&contents, filename)
+
+    let root = Path::new(filename).parent().unwrap_or_else(|| Path::new(""));
+    compile_with_resolver(&contents, filename, &FileResolver::new(root))
 }
 
-/// Compile a String to module
+/// Compile a String to module, it can not import other modules
 pub fn compile_str(string: &str, name: &str) -> Result<Module, GearsError> {
-    compile_ast(parser::ModuleParser::new().parse(lexer::lex(string))?, name)
+    compile_with_resolver(string, name, &MemoryResolver::new())
+}
+
+/// Compile a String to module, the resolver finds the modules it imports
+pub fn compile_with_resolver(
+    string: &str,
+    name: &str,
+    resolver: &dyn ModuleResolver,
+) -> Result<Module, GearsError> {
+    compile_ast(parser::ModuleParser::new().parse(lexer::lex(string))?, name, resolver)
+}
+
+/// The names a module defines, by the name they are imported with
+type Exports = HashMap<String, Symbol>;
+
+/// Compiles the modules that are imported while a module is compiled
+struct Loader<'r> {
+    resolver: &'r dyn ModuleResolver,
+    // The chain of imports being compiled, used to find import cycles
+    loading: Vec<String>,
+    loaded: HashMap<String, Exports>,
+    // Every user defined type compiled so far by its type name, a module can
+    // only name the types it defines or imports but its values can have any
+    types: Exports,
 }
 
 /// Compiles AST to Module and Bytecode
-fn compile_ast(ast: Vec<Box<ModStmtAst>>, name: &str) -> Result<Module, GearsError> {
+///
+/// Imported modules are compiled into the same module, their functions and
+/// globals are named `module.name`.
+fn compile_ast(
+    ast: Vec<Box<ModStmtAst>>,
+    name: &str,
+    resolver: &dyn ModuleResolver,
+) -> Result<Module, GearsError> {
     let mut module_builder = ModuleBuilder::new(String::from(name));
+    let module_name = Path::new(name)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(name);
+    let mut loader = Loader {
+        resolver,
+        loading: vec![module_name.to_string()],
+        loaded: HashMap::new(),
+        types: HashMap::new(),
+    };
+
+    compile_unit(&ast, "", &mut loader, &mut module_builder)?;
+
+    let module = module_builder.build();
+    vm::initialize_module(&module)?;
+    Ok(module)
+}
+
+/// Compiles an imported module once, returning the names it defines
+fn load_module(
+    name: &String,
+    loader: &mut Loader,
+    module_builder: &mut ModuleBuilder,
+) -> Result<Exports, GearsError> {
+    if let Some(exports) = loader.loaded.get(name) {
+        return Ok(exports.clone());
+    }
+
+    if let Some(start) = loader.loading.iter().position(|m| m == name) {
+        let mut cycle = loader.loading[start..].to_vec();
+        cycle.push(name.clone());
+
+        return Err(GearsError::ImportError(format!(
+            "Import cycle: {}",
+            cycle.join(" -> ")
+        )));
+    }
+
+    let source = loader.resolver.resolve(name)?;
+    let ast = parser::ModuleParser::new().parse(lexer::lex(&source))?;
+
+    loader.loading.push(name.clone());
+    let exports = compile_unit(&ast, &format!("{}.", name), loader, module_builder)?;
+    loader.loading.pop();
+
+    loader.loaded.insert(name.clone(), exports.clone());
+    Ok(exports)
+}
+
+/// Compiles the statements of one module, returning the names it defines
+///
+/// The functions and globals of the module are named with the prefix.
+fn compile_unit(
+    ast: &[Box<ModStmtAst>],
+    prefix: &str,
+    loader: &mut Loader,
+    mut module_builder: &mut ModuleBuilder,
+) -> Result<Exports, GearsError> {
     let mut symbol_table = SymbolTable::new_global();
+    // Names that were defined by other modules
+    let mut imported = HashSet::new();
+
+    for mod_stmt in ast {
+        if let ModStmtAst::Import { module, names } = mod_stmt.as_ref() {
+            let exports = load_module(module, loader, module_builder)?;
+
+            let names = match names {
+                Some(names) => names,
+                None => {
+                    expect_new_name(&symbol_table, module)?;
+                    symbol_table.def_module(module.clone(), exports);
+                    imported.insert(module.clone());
+                    continue;
+                }
+            };
+
+            for name in names {
+                let symbol = match exports.get(name) {
+                    Some(symbol) => symbol,
+                    None => {
+                        return Err(GearsError::SymbolNotFound(format!("{}.{}", module, name)))
+                    }
+                };
+
+                expect_new_name(&symbol_table, name)?;
+                symbol_table.def_symbol(name.clone(), symbol.clone());
+                imported.insert(name.clone());
+            }
+        }
+    }
+
+    // The fields and variants of a value are found by its type name, the type
+    // names of imported types are not valid identifiers so they can not be
+    // used in the source
+    for (type_name, symbol) in &loader.types {
+        symbol_table.def_symbol(type_name.clone(), symbol.clone());
+        imported.insert(type_name.clone());
+    }
+
+    // Types are declared before any types are resolved, so they can refer to
+    // each other in any order
+    let mut type_indices = HashMap::new();
+
+    for mod_stmt in ast {
+        match mod_stmt.as_ref() {
+            ModStmtAst::StructDef { name, fields } => {
+                expect_new_type(&symbol_table, name)?;
+
+                let mut field_names: Vec<&Name> = Vec::new();
+
+                for field in fields {
+                    if field.default().is_some() {
+                        return Err(GearsError::TypeError(format!(
+                            "{}.{} can not have a default value",
+                            name,
+                            field.name()
+                        )));
+                    }
+
+                    if field_names.contains(&field.name()) {
+                        return Err(GearsError::TypeError(format!(
+                            "{} has more than one field named {}",
+                            name,
+                            field.name()
+                        )));
+                    }
+
+                    field_names.push(field.name());
+                }
+
+                let type_name = format!("{}{}", prefix, name);
+                let index = module_builder.add_struct(
+                    type_name.clone(),
+                    fields.iter().map(|f| f.name().clone()).collect(),
+                );
+                symbol_table.def_struct(name.clone(), type_name, index, Vec::new());
+                type_indices.insert(name, index);
+            }
+            ModStmtAst::EnumDef { name, variants } => {
+                expect_new_type(&symbol_table, name)?;
+
+                let mut variant_names: Vec<&Name> = Vec::new();
+
+                for (variant, _) in variants {
+                    if variant_names.contains(&variant) {
+                        return Err(GearsError::TypeError(format!(
+                            "{} has more than one variant named {}",
+                            name, variant
+                        )));
+                    }
+
+                    expect_new_type(&symbol_table, variant)?;
+                    variant_names.push(variant);
+                }
+
+                let type_name = format!("{}{}", prefix, name);
+                let index = module_builder.add_enum(
+                    type_name.clone(),
+                    variants
+                        .iter()
+                        .map(|(variant, payload)| (variant.clone(), payload.len()))
+                        .collect(),
+                )?;
+                // The payload types are filled in once every type is declared
+                let placeholders = variants
+                    .iter()
+                    .map(|(variant, payload)| (variant.clone(), vec![Vec::new(); payload.len()]))
+                    .collect();
+                symbol_table.def_enum(name.clone(), type_name, index, placeholders);
+                type_indices.insert(name, index);
+            }
+            _ => {}
+        }
+    }
 
     // Add all the top level functions to the scope before parsing so we can
     // use them during parse as they will resolve
-    for ref mod_stmt in &ast {
+    for mod_stmt in ast {
         match mod_stmt.as_ref() {
             ModStmtAst::FunctionDef {
                 name,
+                type_params,
                 args,
                 return_type,
                 ..
             } => {
-                let mut arg_types = HashMap::new();
+                let type_scope = type_param_scope(&symbol_table, type_params)?;
+                let mut arg_types = Vec::new();
+                let mut defaults = Vec::new();
+                let mut params = Vec::new();
+
+                for arg in args {
+                    let types = resolve_types(arg.arg_types(), &type_scope)?;
+
+                    let default = arg.default().map(|_| {
+                        module_builder.declare_function(
+                            default_name(prefix, name, arg.name()),
+                            Vec::new(),
+                            types.clone(),
+                        )
+                    });
+                    defaults.push(default);
+                    params.push(Param::new(arg.name().clone(), types.clone(), default));
+                    arg_types.push((arg.name().clone(), types));
+                }
+
+                expect_new_name(&symbol_table, name)?;
+                let return_types = resolve_types(return_type, &type_scope)?;
+                let index = module_builder.declare_function(
+                    format!("{}{}", prefix, name),
+                    params,
+                    return_types.clone(),
+                );
+                symbol_table.def_fn(
+                    name.clone(),
+                    index,
+                    type_params.clone(),
+                    arg_types,
+                    defaults,
+                    return_types,
+                );
+            }
+            ModStmtAst::StructDef { name, fields } => {
+                let mut field_types = Vec::new();
+
+                for field in fields {
+                    let types = resolve_types(field.arg_types(), &symbol_table)?;
+                    field_types.push((field.name().clone(), types));
+                }
+
+                module_builder.set_field_types(
+                    type_indices[name],
+                    field_types.iter().map(|(_, types)| types.clone()).collect(),
+                );
+                symbol_table.def_struct(
+                    name.clone(),
+                    format!("{}{}", prefix, name),
+                    type_indices[name],
+                    field_types,
+                );
+            }
+            ModStmtAst::EnumDef { name, variants } => {
+                let mut variant_types = Vec::new();
+
+                for (variant, payload) in variants {
+                    let mut payload_types = Vec::new();
+
+                    for types in payload {
+                        payload_types.push(resolve_types(types, &symbol_table)?);
+                    }
+
+                    variant_types.push((variant.clone(), payload_types));
+                }
+
+                module_builder.set_payload_types(
+                    type_indices[name],
+                    variant_types.iter().map(|(_, payload)| payload.clone()).collect(),
+                );
+                symbol_table.def_enum(
+                    name.clone(),
+                    format!("{}{}", prefix, name),
+                    type_indices[name],
+                    variant_types,
+                );
+            }
+            ModStmtAst::GlobalDef { .. } | ModStmtAst::Import { .. } => {}
+        }
+    }
+
+    let types: Vec<(String, Symbol)> = symbol_table
+        .symbols()
+        .iter()
+        .filter(|(name, _)| !imported.contains(*name))
+        .filter_map(|(_, symbol)| match symbol.get_type() {
+            SymbolType::Struct { type_name, .. } | SymbolType::Enum { type_name, .. } => {
+                Some((type_name.clone(), symbol.clone()))
+            }
+            _ => None,
+        })
+        .collect();
+
+    for (type_name, symbol) in types {
+        // Values of the types of an imported module are typed by the type name
+        if !prefix.is_empty() {
+            symbol_table.def_symbol(type_name.clone(), symbol.clone());
+            imported.insert(type_name.clone());
+        }
+
+        loader.types.insert(type_name, symbol);
+    }
+
+    // Globals are set in the order they are declared, so the value of a
+    // global can only use the globals declared before it
+    let has_globals = ast.iter().any(|mod_stmt| match mod_stmt.as_ref() {
+        ModStmtAst::GlobalDef { .. } => true,
+        _ => false,
+    });
+
+    if has_globals {
+        module_builder.start_initializer();
+
+        for mod_stmt in ast {
+            if let ModStmtAst::GlobalDef {
+                name,
+                types,
+                expr,
+                mutable,
+            } = mod_stmt.as_ref()
+            {
+                expect_new_name(&symbol_table, name)?;
+
+                let expr_types = {
+                    let mut init_scope = (&symbol_table).push();
+                    visit_expr(expr, &mut init_scope, &mut module_builder)?
+                };
+
+                let types = match types {
+                    Some(types) => {
+                        let types = resolve_types(types, &symbol_table)?;
+
+                        if !types_assignable(&expr_types, &types) {
+                            return Err(GearsError::TypeError(format!(
+                                "{:?} is not compatible with {:?}",
+                                types, expr_types
+                            )));
+                        }
+
+                        types
+                    }
+                    None => infer_types(&[name], expr_types)?,
+                };
+
+                let index = module_builder.add_global(
+                    format!("{}{}", prefix, name),
+                    types.clone(),
+                    *mutable,
+                );
+                module_builder.store_global(index)?;
+                symbol_table.def_global(name.clone(), index, types, *mutable);
+            }
+        }
+
+        module_builder.finish_initializer();
+    }
+
+    for mod_stmt in ast {
+        match mod_stmt.as_ref() {
+            ModStmtAst::FunctionDef {
+                name,
+                type_params,
+                exprs,
+                args,
+                return_type,
+            } => {
+                let type_scope = type_param_scope(&symbol_table, type_params)?;
+
+                // Defaults are computed in the module scope when a call leaves the arg out
+                for arg in args {
+                    if let Some(default) = arg.default() {
+                        let arg_types = resolve_types(arg.arg_types(), &type_scope)?;
+
+                        module_builder.start_function(default_name(prefix, name, arg.name()), 0);
+                        let default_types = {
+                            let mut default_scope = (&type_scope).push_function(arg_types.clone());
+                            visit_expr(default, &mut default_scope, &mut module_builder)?
+                        };
+
+                        if !types_assignable(&default_types, &arg_types) {
+                            return Err(GearsError::TypeError(format!(
+                                "The default of {} is {:?}, expected: {:?}",
+                                arg.name(),
+                                default_types,
+                                arg_types
+                            )));
+                        }
+
+                        module_builder.finish_function();
+                    }
+                }
+
+                module_builder.start_function(format!("{}{}", prefix, name), args.len());
+                let return_types = resolve_types(return_type, &type_scope)?;
+                let mut local_scope = (&type_scope).push_function(return_types.clone());
+
+                for arg in args {
+                    let types = resolve_types(arg.arg_types(), &type_scope)?;
+                    local_scope.def_variable(arg.name().clone(), types);
+                }
+
+                visit_function_body(exprs, &return_types, &mut local_scope, &mut module_builder)?;
+                module_builder.finish_function();
+            }
+            ModStmtAst::StructDef { .. }
+            | ModStmtAst::EnumDef { .. }
+            | ModStmtAst::GlobalDef { .. }
+            | ModStmtAst::Import { .. } => {}
+        }
+    }
+
+    Ok(symbol_table
+        .symbols()
+        .iter()
+        .filter(|(name, _)| !imported.contains(*name))
+        .map(|(name, symbol)| (name.clone(), symbol.clone()))
+        .collect())
+}
+
+/// Pushes a scope where the type params of a generic function can be used as types
+///
+/// The type params are erased when the function is compiled, a call checks the
+/// args with the types the params are inferred as.
+fn type_param_scope<'a>(
+    scope: &'a SymbolTable<'a>,
+    type_params: &[Name],
+) -> Result<SymbolTable<'a>, GearsError> {
+    let mut type_scope = scope.push();
+
+    for name in type_params {
+        expect_new_type(&type_scope, name)?;
+        type_scope.def_type_param(name.clone());
+    }
+
+    Ok(type_scope)
+}
+
+/// The name of the function that computes the default value of an arg
+fn default_name(prefix: &str, function: &String, arg: &String) -> String {
+    format!("@{}{}.{}", prefix, function, arg)
+}
+
+/// Visits the body of a function whose args are already defined in the scope
+fn visit_function_body(
+    exprs: &Stmts,
+    return_types: &Types,
+    scope: &mut SymbolTable,
+    module_builder: &mut ModuleBuilder,
+) -> Result<(), GearsError> {
+    // Explicit return statements are checked as they are visited
+    let block_type = visit_block(exprs, scope, module_builder)?;
+
+    if !types_assignable(&block_type, return_types) {
+        return Err(GearsError::TypeError(format!(
+            "{:?} is not compatible with {:?}",
+            return_types, block_type
+        )));
+    }
+
+    Ok(())
+}
+
+/// Calls the function value on the stack, checking the args against its type
+fn visit_indirect_call(
+    types: &Types,
+    args: &FnArgs,
+    kwargs: &KwArgs,
+    scope: &mut SymbolTable,
+    module_builder: &mut ModuleBuilder,
+) -> Result<Types, GearsError> {
+    let (arg_types, return_types) = match types.as_slice() {
+        [t] if t.function_signature().is_some() => t.function_signature().unwrap(),
+        _ => {
+            return Err(GearsError::TypeError(format!(
+                "Only functions can be called. Found: {:?}",
+                types
+            )))
+        }
+    };
+
+    if !kwargs.is_empty() {
+        return Err(GearsError::TypeError(
+            "Function values only take args by position".to_string(),
+        ));
+    }
+
+    if args.len() != arg_types.len() {
+        return Err(GearsError::TypeError(format!(
+            "The function takes {} args, {} were given",
+            arg_types.len(),
+            args.len()
+        )));
+    }
+
+    for (arg, types) in args.iter().zip(arg_types) {
+        let given = visit_expr(arg, scope, module_builder)?;
+
+        if !types_assignable(&given, types) {
+            return Err(GearsError::TypeError(format!(
+                "{:?} is not compatible with {:?}",
+                types, given
+            )));
+        }
+    }
+
+    module_builder.call_indirect(args.len() as u8);
+    Ok(return_types.clone())
+}
+
+/// Loads the value of a resolved name
+fn visit_symbol(
+    name: &String,
+    symbol: &Symbol,
+    is_global: bool,
+    module_builder: &mut ModuleBuilder,
+) -> Result<Types, GearsError> {
+    let res = match symbol.get_type() {
+        &SymbolType::Function {
+            ref type_params,
+            ref arg_types,
+            ref return_types,
+            ..
+        } => {
+            if !type_params.is_empty() {
+                return Err(GearsError::TypeError(format!(
+                    "{} is generic, it can only be called",
+                    name
+                )));
+            }
+
+            module_builder.load_function(*symbol.get_index(), 0)?;
+            vec![Type::new_function(
+                arg_types.iter().map(|(_, types)| types.clone()).collect(),
+                return_types.clone(),
+            )]
+        }
+        &SymbolType::Variable { ref types, .. } => {
+            Slot::new(*symbol.get_index(), is_global).load(module_builder)?;
+            types.clone()
+        }
+        &SymbolType::Variant {
+            ref enum_name,
+            variant_index,
+            ref payload,
+        } => {
+            if !payload.is_empty() {
+                return Err(GearsError::TypeError(format!(
+                    "{} takes {} values, none were given",
+                    name,
+                    payload.len()
+                )));
+            }
+
+            module_builder.build_variant(*symbol.get_index(), variant_index)?;
+            vec![Type::Named(enum_name.clone())]
+        }
+        &SymbolType::Struct { .. } | &SymbolType::Enum { .. } | &SymbolType::TypeParam => {
+            return Err(GearsError::TypeError(format!("{} is a type, not a value", name)));
+        }
+        &SymbolType::Module { .. } => {
+            return Err(GearsError::TypeError(format!("{} is a module, not a value", name)));
+        }
+    };
+
+    Ok(res)
+}
+
+/// Calls a resolved name, variables are called as function values
+fn visit_call(
+    name: &String,
+    symbol: &Symbol,
+    is_global: bool,
+    args: &FnArgs,
+    kwargs: &KwArgs,
+    scope: &mut SymbolTable,
+    mut module_builder: &mut ModuleBuilder,
+) -> Result<Types, GearsError> {
+    let res = match symbol.get_type() {
+        &SymbolType::Variable { .. } => {
+            let types = visit_symbol(name, symbol, is_global, module_builder)?;
+            return visit_indirect_call(&types, args, kwargs, scope, module_builder);
+        }
+        // Functions are only defined at the module level
+        &SymbolType::Function {
+            ref type_params,
+            ref arg_types,
+            ref defaults,
+            ref return_types,
+        } => {
+            // Defaults were checked against the arg types with the function
+            let params: Vec<(&String, Option<usize>)> = arg_types
+                .iter()
+                .map(|(arg, _)| arg)
+                .zip(defaults.iter().cloned())
+                .collect();
+            let given_types = visit_call_args(
+                name,
+                &params,
+                "arg",
+                args,
+                kwargs,
+                scope,
+                &mut module_builder,
+            )?;
+
+            // The type params of a generic function are inferred from the args
+            let mut substitution = Substitution::new();
+
+            for ((_, types), given) in arg_types.iter().zip(&given_types) {
+                if let Some(given) = given {
+                    infer_type_params(types, given, &mut substitution);
+                }
+            }
+
+            for ((arg, types), given) in arg_types.iter().zip(&given_types) {
+                let (types, given) = match given {
+                    Some(given) => (substitute_types(types, &substitution), given),
+                    None => continue,
+                };
+
+                if !types_assignable(given, &types) {
+                    return Err(GearsError::TypeError(format!(
+                        "{} expects the arg {} to be {:?}, found: {:?}{}",
+                        name,
+                        arg,
+                        types,
+                        given,
+                        describe_substitution(type_params, &substitution)
+                    )));
+                }
+            }
+
+            for param in type_params {
+                if !substitution.contains_key(param)
+                    && return_types.iter().any(|t| t.has_type_param(param))
+                {
+                    return Err(GearsError::TypeError(format!(
+                        "The type of {} can not be inferred from the args of {}{}",
+                        param,
+                        name,
+                        describe_substitution(type_params, &substitution)
+                    )));
+                }
+            }
+
+            module_builder.call_fn(*symbol.get_index(), arg_types.len() as u8)?;
+            substitute_types(return_types, &substitution)
+        }
+        &SymbolType::Struct {
+            ref type_name,
+            ref fields,
+        } => {
+            let params: Vec<(&String, Option<usize>)> =
+                fields.iter().map(|(field, _)| (field, None)).collect();
+            let given_types = visit_call_args(
+                name,
+                &params,
+                "field",
+                args,
+                kwargs,
+                scope,
+                &mut module_builder,
+            )?;
+
+            // Struct fields have no defaults, so every field was given
+            for ((field, field_types), arg_types) in fields.iter().zip(given_types) {
+                let arg_types = arg_types.unwrap_or_default();
+
+                if !types_assignable(&arg_types, field_types) {
+                    return Err(GearsError::TypeError(format!(
+                        "{}.{} is {:?}, found: {:?}",
+                        name, field, field_types, arg_types
+                    )));
+                }
+            }
+
+            module_builder.build_struct(*symbol.get_index())?;
+            vec![Type::Named(type_name.clone())]
+        }
+        &SymbolType::Variant {
+            ref enum_name,
+            variant_index,
+            ref payload,
+        } => {
+            if !kwargs.is_empty() {
+                return Err(GearsError::TypeError(format!(
+                    "{} only takes values by position",
+                    name
+                )));
+            }
+
+            if args.len() != payload.len() {
+                return Err(GearsError::TypeError(format!(
+                    "{} takes {} values, {} were given",
+                    name,
+                    payload.len(),
+                    args.len()
+                )));
+            }
+
+            for (payload_types, arg) in payload.iter().zip(args) {
+                let arg_types = visit_expr(arg, scope, &mut module_builder)?;
+
+                if !types_assignable(&arg_types, payload_types) {
+                    return Err(GearsError::TypeError(format!(
+                        "{:?} is not compatible with {:?}",
+                        payload_types, arg_types
+                    )));
+                }
+            }
+
+            module_builder.build_variant(*symbol.get_index(), variant_index)?;
+            vec![Type::Named(enum_name.clone())]
+        }
+        &SymbolType::Enum { .. } | &SymbolType::Module { .. } | &SymbolType::TypeParam => {
+            return Err(GearsError::TypeError(format!("{} is not callable", name)));
+        }
+    };
+
+    Ok(res)
+}
+
+/// Describes what the type params of a call were inferred as, for errors
+///
+/// Returns an empty string unless the function is generic.
+fn describe_substitution(type_params: &[String], substitution: &Substitution) -> String {
+    if type_params.is_empty() {
+        return String::new();
+    }
+
+    let params: Vec<String> = type_params
+        .iter()
+        .map(|param| match substitution.get(param) {
+            Some(types) => format!("{} = {:?}", param, types),
+            None => format!("{} = unknown", param),
+        })
+        .collect();
+
+    format!(", with {}", params.join(", "))
+}
+
+/// Visits the args of a call in the order they were written and leaves their
+/// values on the stack in the order of the params
+///
+/// Args given by name that are written in a different order than the params
+/// are kept in hidden slots until every arg was visited. The defaults of the
+/// params that were left out are computed after the written args. Returns the
+/// types of each param's arg, `None` where the default is used.
+fn visit_call_args(
+    name: &String,
+    params: &[(&String, Option<usize>)],
+    kind: &str,
+    args: &FnArgs,
+    kwargs: &KwArgs,
+    scope: &mut SymbolTable,
+    mut module_builder: &mut ModuleBuilder,
+) -> Result<Vec<Option<Types>>, GearsError> {
+    let names: Vec<&String> = params.iter().map(|(param, _)| *param).collect();
+    let given = order_args(name, &names, args, kwargs)?;
+
+    for ((param, default), given) in params.iter().zip(&given) {
+        if given.is_none() && default.is_none() {
+            return Err(GearsError::TypeError(format!(
+                "{} is missing the {} {}",
+                name, kind, param
+            )));
+        }
+    }
+
+    let mut arg_scope = (&scope).push();
+    let mut given_types = vec![None; params.len()];
+
+    for (index, arg) in args.iter().enumerate() {
+        given_types[index] = Some(visit_expr(arg, &mut arg_scope, &mut module_builder)?);
+    }
+
+    let positions: Vec<usize> = kwargs
+        .iter()
+        .filter_map(|(arg, _)| names.iter().position(|param| *param == arg))
+        .collect();
+    let in_order = positions
+        .iter()
+        .enumerate()
+        .all(|(offset, index)| *index == args.len() + offset);
+    let mut slots = vec![None; params.len()];
+
+    for ((arg, expr), index) in kwargs.iter().zip(positions) {
+        let types = visit_expr(expr, &mut arg_scope, &mut module_builder)?;
+
+        if !in_order {
+            let slot = arg_scope.def_variable(format!("@{}", arg), types.clone());
+            module_builder.store_fast(slot);
+            slots[index] = Some(slot);
+        }
+
+        given_types[index] = Some(types);
+    }
+
+    for index in args.len()..params.len() {
+        match (slots[index], params[index].1) {
+            (Some(slot), _) => module_builder.load_fast(slot),
+            (None, Some(default)) if given_types[index].is_none() => {
+                module_builder.call_fn(default, 0)?
+            }
+            _ => {}
+        }
+    }
+
+    Ok(given_types)
+}
+
+/// Matches the args of a call to the declared params, by position and then by name
+///
+/// The args are returned in the order of the params, `None` where a param
+/// was left out.
+fn order_args<'a>(
+    name: &String,
+    params: &[&String],
+    args: &'a FnArgs,
+    kwargs: &'a KwArgs,
+) -> Result<Vec<Option<&'a ExprAst>>, GearsError> {
+    if args.len() > params.len() {
+        return Err(GearsError::TypeError(format!(
+            "{} takes {} args, {} were given",
+            name,
+            params.len(),
+            args.len()
+        )));
+    }
+
+    let mut given: Vec<Option<&ExprAst>> = args.iter().map(Some).collect();
+    given.resize(params.len(), None);
+
+    for (arg, expr) in kwargs {
+        match params.iter().position(|param| *param == arg) {
+            Some(index) if given[index].is_none() => given[index] = Some(expr),
+            Some(_) => {
+                return Err(GearsError::TypeError(format!(
+                    "{} was given more than once to {}",
+                    arg, name
+                )))
+            }
+            None => {
+                return Err(GearsError::TypeError(format!(
+                    "{} has no arg named {}",
+                    name, arg
+                )))
+            }
+        }
+    }
+
+    Ok(given)
+}
+
+/// Resolves `module.name` if the target is an imported module
+fn resolve_qualified(
+    scope: &SymbolTable,
+    target: &ExprAst,
+    name: &String,
+) -> Result<Option<Symbol>, GearsError> {
+    let module = match target {
+        ExprAst::Variable(module) => module,
+        _ => return Ok(None),
+    };
+
+    let symbols = match scope.resolve(module).0.map(|s| s.get_type()) {
+        Some(SymbolType::Module { symbols }) => symbols,
+        _ => return Ok(None),
+    };
+
+    match symbols.get(name) {
+        Some(symbol) => Ok(Some(symbol.clone())),
+        None => Err(GearsError::SymbolNotFound(format!("{}.{}", module, name))),
+    }
+}
+
+/// Returns an error if a user defined type or variant can not use the name
+fn expect_new_type(scope: &SymbolTable, name: &String) -> Result<(), GearsError> {
+    if BUILTIN_TYPES.contains(&name.as_str()) {
+        return Err(GearsError::TypeError(format!(
+            "{} is a built in type and can not be redefined",
+            name
+        )));
+    }
+
+    if scope.resolve(name).0.is_some() {
+        return Err(GearsError::TypeError(format!("{} is already defined", name)));
+    }
+
+    Ok(())
+}
+
+/// Returns an error if the name is already defined in the scope
+fn expect_new_name(scope: &SymbolTable, name: &String) -> Result<(), GearsError> {
+    if scope.resolve(name).0.is_some() {
+        return Err(GearsError::TypeError(format!("{} is already defined", name)));
+    }
+
+    Ok(())
+}
+
+/// Resolves the types written in the source
+///
+/// Every name must be a built in type or a struct or enum in the scope.
+fn resolve_types(types: &TypesAst, scope: &SymbolTable) -> Result<Types, GearsError> {
+    let mut res = Vec::new();
+
+    for t in types {
+        res.extend(resolve_type(t, scope)?);
+    }
+
+    Ok(normalize_types(res))
+}
+
+/// An optional type resolves to a union with none, any other type to a single type
+fn resolve_type(t: &TypeAst, scope: &SymbolTable) -> Result<Types, GearsError> {
+    let res = match t {
+        TypeAst::None => Type::None,
+        TypeAst::Name(name, location) => resolve_type_name(name, location, scope)?,
+        TypeAst::Qualified(module, name, location) => {
+            resolve_qualified_type(module, name, location, scope)?
+        }
+        TypeAst::Optional(t) => {
+            let mut types = resolve_type(t, scope)?;
+            types.push(Type::None);
+            return Ok(types);
+        }
+        TypeAst::List(elements) => Type::new_list(resolve_types(elements, scope)?),
+        TypeAst::Map(keys, values) => {
+            let keys = resolve_types(keys, scope)?;
+            expect_hashable(&keys)?;
+            Type::new_map(keys, resolve_types(values, scope)?)
+        }
+        TypeAst::Tuple(items) => {
+            let mut item_types = Vec::new();
+
+            for item in items {
+                item_types.push(resolve_types(item, scope)?);
+            }
+
+            Type::new_tuple(item_types)
+        }
+        TypeAst::Function(args, returns) => {
+            let mut arg_types = Vec::new();
+
+            for arg in args {
+                arg_types.push(resolve_types(arg, scope)?);
+            }
+
+            Type::new_function(arg_types, resolve_types(returns, scope)?)
+        }
+    };
+
+    Ok(vec![res])
+}
+
+fn resolve_type_name(
+    name: &Name,
+    location: &lexer::Span,
+    scope: &SymbolTable,
+) -> Result<Type, GearsError> {
+    let message = match name.as_str() {
+        "int" => return Ok(Type::Int),
+        "float" => return Ok(Type::Float),
+        "bool" => return Ok(Type::Bool),
+        "str" => return Ok(Type::Str),
+        "list" | "map" | "tuple" | "function" => format!(
+            "{} needs the types it holds, like [int], {{str: int}}, (int, str) or (int) -> int",
+            name
+        ),
+        _ => match scope.resolve(name).0.map(|s| s.get_type()) {
+            Some(SymbolType::Struct { type_name, .. }) | Some(SymbolType::Enum { type_name, .. }) => {
+                return Ok(Type::Named(type_name.clone()))
+            }
+            Some(SymbolType::TypeParam) => return Ok(Type::Param(name.clone())),
+            Some(_) => format!("{} is not a type", name),
+            None => format!("Unknown type {}", name),
+        },
+    };
+
+    Err(GearsError::CompileError {
+        location: location.clone(),
+        message,
+    })
+}
+
+/// Resolves `module.Type`, a struct or enum of an imported module
+fn resolve_qualified_type(
+    module: &Name,
+    name: &Name,
+    location: &lexer::Span,
+    scope: &SymbolTable,
+) -> Result<Type, GearsError> {
+    let message = match scope.resolve(module).0.map(|s| s.get_type()) {
+        Some(SymbolType::Module { symbols }) => match symbols.get(name).map(|s| s.get_type()) {
+            Some(SymbolType::Struct { type_name, .. }) | Some(SymbolType::Enum { type_name, .. }) => {
+                return Ok(Type::Named(type_name.clone()))
+            }
+            Some(_) => format!("{}.{} is not a type", module, name),
+            None => format!("Unknown type {}.{}", module, name),
+        },
+        _ => format!("{} is not an imported module", module),
+    };
+
+    Err(GearsError::CompileError {
+        location: location.clone(),
+        message,
+    })
+}
+
+/// Returns an error unless the types are a single int
+fn expect_int(types: &Types, name: &str) -> Result<(), GearsError> {
+    if *types != vec![Type::Int] {
+        return Err(GearsError::TypeError(format!(
+            "{} must be an int. Found: {:?}",
+            name, types
+        )));
+    }
+
+    Ok(())
+}
+
+/// Returns an error unless every type can be used as a map key
+fn expect_hashable(types: &Types) -> Result<(), GearsError> {
+    if !types.iter().all(|t| t.is_hashable()) {
+        return Err(GearsError::TypeError(format!(
+            "Map keys must be an int, str or bool. Found: {:?}",
+            types
+        )));
+    }
+
+    Ok(())
+}
+
+/// Returns the types of the items in a list or string, or the keys of a map
+fn item_types(types: &Types, action: &str) -> Result<Types, GearsError> {
+    let mut res = Vec::new();
+
+    for t in types {
+        match (t.list_elements(), t.map_entries(), t.tuple_items()) {
+            (Some(elements), _, _) => res.extend(elements.iter().cloned()),
+            (_, Some((keys, _)), _) => res.extend(keys.iter().cloned()),
+            (_, _, Some(items)) => res.extend(items.iter().flat_map(|i| i.iter().cloned())),
+            _ if *t == Type::Str => res.push(Type::Str),
+            _ => {
+                return Err(GearsError::TypeError(format!(
+                    "Only lists, strings, tuples and maps can be {}. Found: {:?}",
+                    action, types
+                )))
+            }
+        }
+    }
+
+    Ok(normalize_types(res))
+}
+
+/// Returns the types of the values found by indexing into a list, string or map
+fn indexed_types(types: &Types, index_types: &Types) -> Result<Types, GearsError> {
+    let mut res = Vec::new();
+
+    for t in types {
+        match t.map_entries() {
+            Some((keys, values)) => {
+                if !types_assignable(index_types, keys) {
+                    return Err(GearsError::TypeError(format!(
+                        "Map key must be {:?}. Found: {:?}",
+                        keys, index_types
+                    )));
+                }
+
+                res.extend(values.iter().cloned());
+            }
+            None => {
+                expect_int(index_types, "Index")?;
+                res.extend(item_types(&vec![t.clone()], "indexed")?);
+            }
+        }
+    }
+
+    Ok(normalize_types(res))
+}
+
+/// Returns the types of each item for values that must be tuples of `count` items
+fn unpack_types(types: &Types, count: usize) -> Result<Vec<Types>, GearsError> {
+    let mut res = vec![Vec::new(); count];
+
+    for t in types {
+        match t.tuple_items() {
+            Some(items) if items.len() == count => {
+                for (item_types, item) in res.iter_mut().zip(items) {
+                    item_types.extend(item.iter().cloned());
+                }
+            }
+            _ => {
+                return Err(GearsError::TypeError(format!(
+                    "Only tuples of {} items can be unpacked into {} names. Found: {:?}",
+                    count, count, types
+                )))
+            }
+        }
+    }
+
+    Ok(res.into_iter().map(normalize_types).collect())
+}
+
+/// Stores the value on top of the stack into the names of the binding
+///
+/// New bindings define each name in its own slot, otherwise the names must
+/// exist and accept the types of the value. `mutable` is only used for new
+/// bindings.
+fn visit_binding(
+    binding: &BindingAst,
+    types: &Types,
+    new: bool,
+    mutable: bool,
+    scope: &mut SymbolTable,
+    module_builder: &mut ModuleBuilder,
+) -> Result<(), GearsError> {
+    match binding {
+        BindingAst::Name(name) => {
+            let slot = if new && mutable {
+                Slot::Local(scope.def_variable(name.clone(), types.clone()))
+            } else if new {
+                Slot::Local(scope.def_const(name.clone(), types.clone()))
+            } else {
+                let (slot, _, declared) = resolve_assignable(scope, name)?;
+
+                if !types_assignable(types, &declared) {
+                    return Err(GearsError::TypeError(format!(
+                        "{} is {:?}, it can not be assigned {:?}",
+                        name, declared, types
+                    )));
+                }
+
+                // The variable may not hold its narrowed types anymore
+                scope.widen(name);
+                slot
+            };
+
+            slot.store(module_builder)?;
+        }
+        BindingAst::Tuple(bindings) => {
+            let item_types = unpack_types(types, bindings.len())?;
+            module_builder.unpack_tuple(bindings.len() as u8);
+
+            for (binding, types) in bindings.iter().zip(&item_types) {
+                visit_binding(binding, types, new, mutable, scope, module_builder)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the types of a binding that has no type annotation
+fn infer_types(names: &[&Name], expr_types: Types) -> Result<Types, GearsError> {
+    if expr_types.is_empty() || !expr_types.iter().all(|t| t.is_complete()) {
+        let names: Vec<&str> = names.iter().map(|name| name.as_str()).collect();
+
+        return Err(GearsError::TypeError(format!(
+            "The type of {} can not be inferred from {:?}, it needs a type annotation",
+            names.join(", "),
+            expr_types
+        )));
+    }
+
+    Ok(expr_types)
+}
+
+/// Returns an error if a name appears more than once in a binding
+fn expect_unique_names(binding: &BindingAst) -> Result<(), GearsError> {
+    let mut seen = HashSet::new();
+
+    for name in binding.names() {
+        if !seen.insert(name) {
+            return Err(GearsError::TypeError(format!("{} is bound more than once", name)));
+        }
+    }
+
+    Ok(())
+}
+
+/// Where the value of a variable is kept
+#[derive(Clone, Copy)]
+enum Slot {
+    Local(u8),
+    Global(usize),
+}
+
+impl Slot {
+    fn new(index: usize, is_global: bool) -> Slot {
+        if is_global {
+            Slot::Global(index)
+        } else {
+            // Locals are numbered by the u8 slot count of their scope
+            Slot::Local(index as u8)
+        }
+    }
+
+    fn load(self, module_builder: &mut ModuleBuilder) -> Result<(), GearsError> {
+        match self {
+            Slot::Local(index) => module_builder.load_fast(index),
+            Slot::Global(index) => return module_builder.load_global(index),
+        }
+
+        Ok(())
+    }
+
+    fn store(self, module_builder: &mut ModuleBuilder) -> Result<(), GearsError> {
+        match self {
+            Slot::Local(index) => module_builder.store_fast(index),
+            Slot::Global(index) => return module_builder.store_global(index),
+        }
+
+        Ok(())
+    }
+}
+
+/// One step from a variable to the part of it that is assigned to
+enum PlaceStep<'a> {
+    Index(&'a ExprAst),
+    Field(&'a Name),
+}
+
+/// Adds the steps of a place expression to `steps` and returns its variable
+fn place_steps<'a>(
+    target: &'a ExprAst,
+    steps: &mut Vec<PlaceStep<'a>>,
+) -> Result<&'a Name, GearsError> {
+    match target {
+        ExprAst::Variable(name) => Ok(name),
+        ExprAst::Index { expr, index } => {
+            let name = place_steps(expr, steps)?;
+            steps.push(PlaceStep::Index(index));
+            Ok(name)
+        }
+        ExprAst::Field { expr, field } => {
+            let name = place_steps(expr, steps)?;
+            steps.push(PlaceStep::Field(field));
+            Ok(name)
+        }
+        _ => Err(GearsError::InternalCompilerError(format!(
+            "{:?} can not be assigned to",
+            target
+        ))),
+    }
+}
+
+/// Returns the types of the values an index assignment can store in each of the types
+fn index_target_types(types: &Types, index_types: &Types) -> Result<Vec<Types>, GearsError> {
+    let mut res = Vec::new();
+
+    for t in types {
+        let (target_keys, target_values) = match t.map_entries() {
+            Some((keys, values)) => (keys.clone(), values.clone()),
+            None => match t.list_elements() {
+                Some(elements) => (vec![Type::Int], elements.clone()),
+                None => {
+                    return Err(GearsError::TypeError(format!(
+                        "Only lists and maps support index assignment. Found: {:?}",
+                        types
+                    )))
+                }
+            },
+        };
+
+        if !types_assignable(index_types, &target_keys) {
+            return Err(GearsError::TypeError(format!(
+                "{:?} is not compatible with {:?}",
+                target_keys, index_types
+            )));
+        }
+
+        res.push(target_values);
+    }
+
+    Ok(res)
+}
+
+/// Assigns to a field or index of a variable, e.g. `a[0].x = expr`
+///
+/// Values are copied on write, so every container on the way to the target is
+/// stored back into the one it came from, and the variable gets the outermost.
+fn visit_place_assignment(
+    target: &ExprAst,
+    last_step: PlaceStep,
+    expr: &ExprAst,
+    scope: &mut SymbolTable,
+    mut module_builder: &mut ModuleBuilder,
+) -> Result<Types, GearsError> {
+    let mut steps = Vec::new();
+    let name = place_steps(target, &mut steps)?;
+    let (slot, mut types, _) = resolve_assignable(scope, name)?;
+
+    slot.load(&mut module_builder)?;
+
+    // Read each container on the way while keeping its parent on the stack
+    for step in &steps {
+        match step {
+            PlaceStep::Index(index) => {
+                let index_types = visit_expr(index, scope, &mut module_builder)?;
+                let value_types = index_target_types(&types, &index_types)?;
+
+                module_builder.dup_top(2);
+                module_builder.index();
+                types = normalize_types(value_types.into_iter().flatten().collect());
+            }
+            PlaceStep::Field(field) => {
+                module_builder.dup_top(1);
+                module_builder.get_field(field.to_string())?;
+                types = field_types(scope, &types, field)?;
+            }
+        }
+    }
+
+    // The new value has to fit every type the target could hold
+    let target_types = match last_step {
+        PlaceStep::Index(index) => {
+            let index_types = visit_expr(index, scope, &mut module_builder)?;
+            index_target_types(&types, &index_types)?
+        }
+        PlaceStep::Field(field) => {
+            let mut res = Vec::new();
+
+            for t in &types {
+                res.push(field_types(scope, &vec![t.clone()], field)?);
+            }
+            res
+        }
+    };
+    let expr_types = visit_expr(expr, scope, &mut module_builder)?;
+
+    for target_types in &target_types {
+        if !types_assignable(&expr_types, target_types) {
+            return Err(GearsError::TypeError(format!(
+                "{:?} is not compatible with {:?}",
+                target_types, expr_types
+            )));
+        }
+    }
+
+    for step in steps.iter().chain(Some(&last_step)).rev() {
+        match step {
+            PlaceStep::Index(_) => module_builder.store_index(),
+            PlaceStep::Field(field) => module_builder.set_field(field.to_string())?,
+        }
+    }
+
+    slot.store(&mut module_builder)?;
+    Ok(expr_types)
+}
+
+/// Resolves a variable that is about to be assigned to
+///
+/// Returns its slot, its current types and the types it was declared with.
+fn resolve_assignable(
+    scope: &SymbolTable,
+    name: &String,
+) -> Result<(Slot, Types, Types), GearsError> {
+    let (symbol, is_global) = scope.resolve(name);
+
+    match symbol.map(|s| (s.get_index(), s.get_type())) {
+        Some((
+            index,
+            SymbolType::Variable {
+                types,
+                declared,
+                mutable,
+            },
+        )) => {
+            if !mutable {
+                return Err(GearsError::TypeError(format!(
+                    "{} is a constant and can not be assigned to",
+                    name
+                )));
+            }
+
+            Ok((Slot::new(*index, is_global), types.clone(), declared.clone()))
+        }
+        Some(_) => Err(GearsError::TypeError(format!("{} can not be assigned to", name))),
+        None => Err(GearsError::SymbolNotFound(name.clone())),
+    }
+}
+
+/// Returns the types of a field, every type must be a struct that has the field
+fn field_types(scope: &SymbolTable, types: &Types, field: &str) -> Result<Types, GearsError> {
+    let mut res = Vec::new();
+
+    for t in types {
+        let symbol = t.named().and_then(|name| scope.resolve(name).0);
+
+        let (name, fields) = match (t.named(), symbol.map(|s| s.get_type())) {
+            (Some(name), Some(SymbolType::Struct { fields, .. })) => (name, fields),
+            _ => {
+                return Err(GearsError::TypeError(format!(
+                    "Only structs have fields. Found: {:?}",
+                    types
+                )))
+            }
+        };
+
+        match fields.iter().find(|(name, _)| name == field) {
+            Some((_, field_types)) => res.extend(field_types.iter().cloned()),
+            None => {
+                return Err(GearsError::TypeError(format!(
+                    "{} has no field {}",
+                    name, field
+                )))
+            }
+        }
+    }
+
+    Ok(normalize_types(res))
+}
+
+/// Variables with more precise types and the types they are narrowed to
+type Narrowing = Vec<(Name, Types)>;
+
+/// Returns the narrowed variables for when the condition is true and when it is false
+///
+/// Only comparisons of a variable with `none` narrow, combined with `and`, `or` and `not`.
+fn condition_narrowing(cond: &ExprAst, scope: &SymbolTable) -> (Narrowing, Narrowing) {
+    match cond {
+        ExprAst::Op(left, op @ BinOpAst::EqEq, right)
+        | ExprAst::Op(left, op @ BinOpAst::NotEq, right) => {
+            let name = match (&**left, &**right) {
+                (ExprAst::Variable(name), ExprAst::None)
+                | (ExprAst::None, ExprAst::Variable(name)) => name,
+                _ => return (Vec::new(), Vec::new()),
+            };
+
+            let types = match scope.resolve(name).0.map(|s| s.get_type()) {
+                Some(SymbolType::Variable { types, .. }) if is_optional(types) => types,
+                _ => return (Vec::new(), Vec::new()),
+            };
+
+            let is_none = vec![(name.clone(), vec![Type::None])];
+            let not_none = vec![(name.clone(), without_none(types))];
+
+            match op {
+                BinOpAst::EqEq => (is_none, not_none),
+                _ => (not_none, is_none),
+            }
+        }
+        ExprAst::Op(left, BinOpAst::And, right) => {
+            let mut narrowing = without_assigned(condition_narrowing(left, scope).0, right);
+            narrowing.extend(condition_narrowing(right, scope).0);
+            (narrowing, Vec::new())
+        }
+        ExprAst::Op(left, BinOpAst::Or, right) => {
+            let mut narrowing = without_assigned(condition_narrowing(left, scope).1, right);
+            narrowing.extend(condition_narrowing(right, scope).1);
+            (Vec::new(), narrowing)
+        }
+        ExprAst::UnaryOp(UnaryOpAst::Not, expr) => {
+            let (then_narrowing, else_narrowing) = condition_narrowing(expr, scope);
+            (else_narrowing, then_narrowing)
+        }
+        _ => (Vec::new(), Vec::new()),
+    }
+}
+
+/// Drops the narrowed variables that are reassigned in the expression evaluated after them
+fn without_assigned(narrowing: Narrowing, expr: &ExprAst) -> Narrowing {
+    let mut names = Vec::new();
+    expr.collect_assigned(&mut names);
+
+    narrowing
+        .into_iter()
+        .filter(|(name, _)| !names.contains(name))
+        .collect()
+}
+
+fn apply_narrowing(scope: &mut SymbolTable, narrowing: &Narrowing) {
+    for (name, types) in narrowing {
+        scope.narrow(name, types.clone());
+    }
+}
+
+/// Widens the variables that are reassigned anywhere in the expression
+///
+/// The narrowing of a variable from before the expression may not hold after it,
+/// or on a later pass through a loop.
+fn widen_assigned(expr: &ExprAst, scope: &mut SymbolTable) {
+    let mut names = Vec::new();
+    expr.collect_assigned(&mut names);
+
+    for name in &names {
+        scope.widen(name);
+    }
+}
+
+/// A match pattern after its names have been resolved and its types checked
+enum Pattern<'a> {
+    /// Matches any value of the types, binding it to the name if there is one
+    Wildcard(Option<&'a Name>, Types),
+    Literal(&'a ExprAst, Type),
+    Variant {
+        name: &'a Name,
+        enum_name: String,
+        // The enum's index in the module and the variant's index in the enum
+        enum_index: usize,
+        variant_index: u8,
+        args: Vec<Pattern<'a>>,
+    },
+}
+
+impl<'a> Pattern<'a> {
+    /// Returns None for patterns that match any value
+    fn refutable(&self) -> Option<&Pattern<'a>> {
+        match self {
+            Pattern::Wildcard(..) => None,
+            _ => Some(self),
+        }
+    }
+
+    fn constructor(&self) -> Option<Constructor> {
+        match self {
+            Pattern::Variant {
+                name, enum_name, ..
+            } => Some(Constructor::Variant(enum_name.clone(), (*name).clone())),
+            Pattern::Literal(ExprAst::Bool(b), _) => Some(Constructor::Bool(*b)),
+            Pattern::Literal(ExprAst::None, _) => Some(Constructor::None),
+            _ => None,
+        }
+    }
+
+    /// Returns the types a value can have once it matched the pattern
+    fn narrowed_types(&self, types: &Types) -> Types {
+        match self {
+            Pattern::Wildcard(..) => types.clone(),
+            Pattern::Literal(_, literal_type) => vec![literal_type.clone()],
+            Pattern::Variant { enum_name, .. } => vec![Type::Named(enum_name.clone())],
+        }
+    }
+}
+
+/// One of the values a type with a known number of values can take
+#[derive(PartialEq)]
+enum Constructor {
+    /// The type name of the enum and the name of the variant
+    Variant(String, String),
+    Bool(bool),
+    None,
+}
+
+fn lower_pattern<'a>(
+    pattern: &'a PatternAst,
+    types: &Types,
+    scope: &SymbolTable,
+) -> Result<Pattern<'a>, GearsError> {
+    match pattern {
+        PatternAst::Wildcard => Ok(Pattern::Wildcard(None, types.clone())),
+        PatternAst::Name(name) => match scope.resolve(name).0.map(|s| s.get_type()) {
+            // A variant without a payload is matched rather than shadowed
+            Some(SymbolType::Variant { .. }) => lower_variant(name, &[], types, scope),
+            _ => Ok(Pattern::Wildcard(Some(name), types.clone())),
+        },
+        PatternAst::Variant { name, args } => lower_variant(name, args, types, scope),
+        PatternAst::Literal(expr) => {
+            let literal_type = match expr {
+                ExprAst::Integer(..) | ExprAst::UnaryOp(..) => Type::Int,
+                ExprAst::Float(_) => Type::Float,
+                ExprAst::Str(_) => Type::Str,
+                ExprAst::Bool(_) => Type::Bool,
+                ExprAst::None => Type::None,
+                _ => {
+                    return Err(GearsError::InternalCompilerError(format!(
+                        "{:?} is not a literal pattern",
+                        expr
+                    )))
+                }
+            };
+
+            if !literal_type.is_assignable_to(types) {
+                return Err(GearsError::TypeError(format!(
+                    "A {:?} pattern can never match {:?}",
+                    literal_type, types
+                )));
+            }
+
+            Ok(Pattern::Literal(expr, literal_type))
+        }
+    }
+}
+
+fn lower_variant<'a>(
+    name: &'a Name,
+    args: &'a [PatternAst],
+    types: &Types,
+    scope: &SymbolTable,
+) -> Result<Pattern<'a>, GearsError> {
+    let resolved = scope.resolve(name).0.map(|s| (s.get_index(), s.get_type()));
+
+    let (enum_index, enum_name, variant_index, payload) = match resolved {
+        Some((
+            index,
+            SymbolType::Variant {
+                enum_name,
+                variant_index,
+                payload,
+            },
+        )) => (*index, enum_name.clone(), *variant_index, payload.clone()),
+        _ => {
+            return Err(GearsError::TypeError(format!(
+                "{} is not an enum variant",
+                name
+            )))
+        }
+    };
+
+    if !Type::Named(enum_name.clone()).is_assignable_to(types) {
+        return Err(GearsError::TypeError(format!(
+            "{} is a variant of {} and can never match {:?}",
+            name, enum_name, types
+        )));
+    }
+
+    if args.len() != payload.len() {
+        return Err(GearsError::TypeError(format!(
+            "{} has {} values, the pattern has {}",
+            name,
+            payload.len(),
+            args.len()
+        )));
+    }
+
+    let mut lowered = Vec::new();
+
+    for (arg, arg_types) in args.iter().zip(&payload) {
+        lowered.push(lower_pattern(arg, arg_types, scope)?);
+    }
+
+    Ok(Pattern::Variant {
+        name,
+        enum_name,
+        enum_index,
+        variant_index,
+        args: lowered,
+    })
+}
+
+/// Returns the constructors covering every value of the types, or None if
+/// there are too many values to list and only a wildcard can cover them
+fn constructors(types: &Types, scope: &SymbolTable) -> Option<Vec<(Constructor, Vec<Types>)>> {
+    let mut res = Vec::new();
+
+    for t in types {
+        let name = match t {
+            Type::Bool => {
+                res.push((Constructor::Bool(true), Vec::new()));
+                res.push((Constructor::Bool(false), Vec::new()));
+                continue;
+            }
+            Type::None => {
+                res.push((Constructor::None, Vec::new()));
+                continue;
+            }
+            Type::Named(name) => name,
+            _ => return None,
+        };
+
+        match scope.resolve(name).0.map(|s| s.get_type()) {
+            Some(SymbolType::Enum { variants, .. }) => {
+                for (variant, payload) in variants {
+                    res.push((
+                        Constructor::Variant(name.clone(), variant.clone()),
+                        payload.clone(),
+                    ));
+                }
+            }
+            _ => return None,
+        }
+    }
+
+    Some(res)
+}
+
+/// Checks if the rows of patterns together match every value of the columns
+///
+/// Each row is an arm, a None in a row matches anything. Rows are split by the
+/// constructors of the first column and checked again on the payloads.
+fn is_exhaustive(rows: Vec<Vec<Option<&Pattern>>>, columns: &[Types], scope: &SymbolTable) -> bool {
+    if columns.is_empty() {
+        return !rows.is_empty();
+    }
+
+    let rest = &columns[1..];
+
+    match constructors(&columns[0], scope) {
+        None => {
+            let defaults = rows
+                .iter()
+                .filter(|row| row[0].is_none())
+                .map(|row| row[1..].to_vec())
+                .collect();
+
+            is_exhaustive(defaults, rest, scope)
+        }
+        Some(constructors) => constructors.iter().all(|(constructor, payload)| {
+            let mut specialized = Vec::new();
+
+            for row in &rows {
+                match row[0] {
+                    None => {
+                        let mut new_row = vec![None; payload.len()];
+                        new_row.extend_from_slice(&row[1..]);
+                        specialized.push(new_row);
+                    }
+                    Some(pattern) if pattern.constructor().as_ref() == Some(constructor) => {
+                        let mut new_row = match pattern {
+                            Pattern::Variant { args, .. } => {
+                                args.iter().map(|arg| arg.refutable()).collect()
+                            }
+                            _ => Vec::new(),
+                        };
+                        new_row.extend_from_slice(&row[1..]);
+                        specialized.push(new_row);
+                    }
+                    Some(_) => {}
+                }
+            }
+
+            let mut new_columns = payload.clone();
+            new_columns.extend_from_slice(rest);
+            is_exhaustive(specialized, &new_columns, scope)
+        }),
+    }
+}
+
+/// Splits the rows of the earlier arms along the pattern
+///
+/// Keeps the rows that can match some value the pattern matches. The rows get a
+/// column for each wildcard of the pattern, whose types are added to `columns`.
+fn specialize_rows<'p, 'a: 'p>(
+    rows: Vec<Vec<Option<&'p Pattern<'a>>>>,
+    pattern: &'p Pattern<'a>,
+    columns: &mut Vec<Types>,
+) -> Vec<Vec<Option<&'p Pattern<'a>>>> {
+    // Each row is split into the columns that are done and the ones still to split
+    let mut rows: Vec<_> = rows.into_iter().map(|row| (Vec::new(), row)).collect();
+    let mut pending = vec![pattern];
+
+    while !pending.is_empty() {
+        let pattern = pending.remove(0);
+
+        if let Pattern::Wildcard(_, types) = pattern {
+            columns.push(types.clone());
+
+            for (done, row) in &mut rows {
+                done.push(row.remove(0));
+            }
+            continue;
+        }
+
+        let args: &[Pattern] = match pattern {
+            Pattern::Variant { args, .. } => args,
+            _ => &[],
+        };
+        let constructor = pattern.constructor();
+
+        // Rows with a literal that is not a constructor, like an int, are dropped
+        // as they only match some of the values
+        rows = rows
+            .into_iter()
+            .filter_map(|(done, mut row)| {
+                let mut new_row: Vec<_> = match row.remove(0) {
+                    None => vec![None; args.len()],
+                    Some(other) if constructor.is_some() && other.constructor() == constructor => {
+                        match other {
+                            Pattern::Variant { args, .. } => {
+                                args.iter().map(|arg| arg.refutable()).collect()
+                            }
+                            _ => Vec::new(),
+                        }
+                    }
+                    Some(_) => return None,
+                };
+                new_row.extend(row);
+                Some((done, new_row))
+            })
+            .collect();
+
+        for arg in args.iter().rev() {
+            pending.insert(0, arg);
+        }
+    }
+
+    rows.into_iter().map(|(done, _)| done).collect()
+}
+
+/// Narrows the types of the wildcards in a pattern to the values the earlier arms leave
+///
+/// A type is left out if the earlier arms match every value that would match
+/// the pattern with the wildcard holding that type.
+fn narrow_wildcards(
+    pattern: &mut Pattern,
+    earlier: &[Pattern],
+    scope: &SymbolTable,
+) -> Result<(), GearsError> {
+    let narrowed: Vec<Types> = {
+        let rows = earlier.iter().map(|p| vec![p.refutable()]).collect();
+        let mut columns = Vec::new();
+        let rows = specialize_rows(rows, pattern, &mut columns);
 
-                for arg in args {
-                    arg_types.insert(arg.name().clone(), compile_types(arg.arg_types()));
-                }
+        columns
+            .iter()
+            .enumerate()
+            .map(|(index, types)| {
+                types
+                    .iter()
+                    .filter(|t| {
+                        let mut probe = columns.clone();
+                        probe[index] = vec![(*t).clone()];
+                        !is_exhaustive(rows.clone(), &probe, scope)
+                    })
+                    .cloned()
+                    .collect()
+            })
+            .collect()
+    };
+
+    if narrowed.iter().any(|types| types.is_empty()) {
+        return Err(GearsError::TypeError(
+            "This arm can never match, every value is matched by the arms above".to_string(),
+        ));
+    }
+
+    set_wildcard_types(pattern, &mut narrowed.into_iter());
+    Ok(())
+}
 
-                symbol_table.def_fn(name.clone(), arg_types, compile_types(return_type));
+/// Gives the wildcards of the pattern the types, in the order they are written
+fn set_wildcard_types<I: Iterator<Item = Types>>(pattern: &mut Pattern, types: &mut I) {
+    match pattern {
+        Pattern::Wildcard(_, wildcard_types) => {
+            if let Some(t) = types.next() {
+                *wildcard_types = t;
+            }
+        }
+        Pattern::Variant { args, .. } => {
+            for arg in args {
+                set_wildcard_types(arg, types);
             }
         }
+        Pattern::Literal(..) => {}
     }
+}
 
-    for ref mod_stmt in &ast {
-        match mod_stmt.as_ref() {
-            ModStmtAst::FunctionDef {
-                name,
-                exprs,
-                args,
-                return_type,
-                ..
-            } => {
-                module_builder.start_function(name.clone(), args.len());
-                let mut local_scope = (&symbol_table).push();
-
-                for arg in args {
-                    local_scope.def_variable(arg.name().clone(), compile_types(arg.arg_types()));
-                }
+/// Loads the value at the path of payload indexes into the matched value
+fn load_match_path(match_index: u8, path: &[u8], module_builder: &mut ModuleBuilder) {
+    module_builder.load_fast(match_index);
 
-                let block_type = visit_block(exprs, &mut local_scope, &mut module_builder)?;
-                let return_types = compile_types(return_type);
+    for index in path {
+        module_builder.get_payload(*index);
+    }
+}
 
-                for expr_type in &block_type {
-                    if !return_types.contains(expr_type) {
-                        return Err(GearsError::TypeError(format!(
-                            "{:?} is not compatible with {:?}",
-                            return_types, block_type
-                        )));
-                    }
-                }
+/// Emits the checks for a pattern, each failed check jumps to the next arm
+fn visit_pattern_test(
+    pattern: &Pattern,
+    match_index: u8,
+    path: &mut Vec<u8>,
+    scope: &mut SymbolTable,
+    module_builder: &mut ModuleBuilder,
+    fail_jumps: &mut Vec<usize>,
+) -> Result<(), GearsError> {
+    match pattern {
+        Pattern::Wildcard(..) => {}
+        Pattern::Literal(expr, _) => {
+            load_match_path(match_index, path, module_builder);
+            visit_expr(expr, scope, module_builder)?;
+            module_builder.op_eqeq();
+            fail_jumps.push(module_builder.start_jump_if_false());
+        }
+        Pattern::Variant {
+            enum_index,
+            variant_index,
+            args,
+            ..
+        } => {
+            load_match_path(match_index, path, module_builder);
+            module_builder.is_variant(*enum_index, *variant_index)?;
+            fail_jumps.push(module_builder.start_jump_if_false());
 
-                module_builder.finish_function();
+            for (index, arg) in args.iter().enumerate() {
+                path.push(index as u8);
+                visit_pattern_test(arg, match_index, path, scope, module_builder, fail_jumps)?;
+                path.pop();
             }
         }
     }
 
-    Ok(module_builder.build())
+    Ok(())
 }
 
-fn compile_types(types: &Vec<String>) -> Vec<Type> {
-    let mut types_vec = Vec::new();
+/// Stores the parts of the matched value into the names bound by the pattern
+fn visit_pattern_bindings(
+    pattern: &Pattern,
+    match_index: u8,
+    path: &mut Vec<u8>,
+    scope: &mut SymbolTable,
+    module_builder: &mut ModuleBuilder,
+) {
+    match pattern {
+        Pattern::Wildcard(Some(name), types) => {
+            load_match_path(match_index, path, module_builder);
+            let index = scope.def_variable((*name).clone(), types.clone());
+            module_builder.store_fast(index);
+        }
+        Pattern::Variant { args, .. } => {
+            for (index, arg) in args.iter().enumerate() {
+                path.push(index as u8);
+                visit_pattern_bindings(arg, match_index, path, scope, module_builder);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
 
-    for t in types {
-        types_vec.push(Type::from(t.clone()))
+fn unreachable_error(location: &lexer::Span) -> GearsError {
+    GearsError::CompileError {
+        location: location.clone(),
+        message: "Unreachable code after a return, break or continue".to_string(),
     }
-    types_vec
 }
 
 fn visit_block(
@@ -100,15 +1992,49 @@ fn visit_block(
 ) -> Result<Types, GearsError> {
     let mut local_scope = (&scope).push();
 
-    for stmt in &exprs.0 {
-        visit_stmt(stmt.as_ref(), &mut local_scope, &mut module_builder)?;
+    // An empty list of types means the statement never finishes, e.g. a return
+    let mut diverged = false;
+
+    for (location, stmt) in &exprs.0 {
+        if diverged {
+            return Err(unreachable_error(location));
+        }
+
+        let stmt_types = visit_stmt(stmt.as_ref(), &mut local_scope, &mut module_builder)?;
+        diverged = stmt_types.is_empty();
+
+        if stmt.has_value() {
+            module_builder.pop_top();
+        }
+    }
+
+    if diverged {
+        if let Some((ref location, _)) = exprs.1 {
+            return Err(unreachable_error(location));
+        }
+
+        module_builder.load_none();
+        return Ok(Vec::new());
     }
 
     let last_type = match exprs.1 {
-        Some(ref e) => visit_stmt(e.as_ref(), &mut local_scope, &mut module_builder)?,
+        Some((_, ref e)) if e.has_value() => {
+            visit_stmt(e.as_ref(), &mut local_scope, &mut module_builder)?
+        }
+        Some((_, ref e)) => {
+            // Statements without a value make the block evaluate to none
+            let stmt_types = visit_stmt(e.as_ref(), &mut local_scope, &mut module_builder)?;
+            module_builder.load_none();
+
+            if stmt_types.is_empty() {
+                Vec::new()
+            } else {
+                vec![Type::None]
+            }
+        }
         None => {
             module_builder.load_none();
-            vec![Type::new_none()]
+            vec![Type::None]
         }
     };
 
@@ -123,42 +2049,110 @@ fn visit_stmt(
     match stmt {
         StmtAst::Expr(e) => visit_expr(e, scope, module_builder),
         StmtAst::Assignment {
-            name,
+            binding,
             expr,
             new,
+            mutable,
             types,
         } => {
+            expect_unique_names(binding)?;
             let expr_types = visit_expr(expr, scope, &mut module_builder)?;
 
             let var_types: Types = match types {
                 Some(given_types) => {
-                    let given_types = compile_types(&given_types);
-                    for expr_type in &expr_types {
-                        if !given_types.contains(&expr_type) {
-                            return Err(GearsError::TypeError(format!(
-                                "{:?} is not compatible with {:?}",
-                                given_types, expr_types
-                            )));
-                        }
+                    let given_types = resolve_types(&given_types, scope)?;
+                    if !types_assignable(&expr_types, &given_types) {
+                        return Err(GearsError::TypeError(format!(
+                            "{:?} is not compatible with {:?}",
+                            given_types, expr_types
+                        )));
                     }
                     given_types
                 }
+                None if *new => infer_types(&binding.names(), expr_types)?,
                 None => expr_types,
             };
 
-            let index = if *new {
-                scope.def_variable(name.clone(), compile_types(&types.clone().unwrap()))
-            } else {
-                let (symbol, _is_global) = scope.resolve(name);
+            visit_binding(
+                binding,
+                &var_types,
+                *new,
+                *mutable,
+                scope,
+                &mut module_builder,
+            )?;
+            Ok(var_types)
+        }
+        StmtAst::IndexAssignment {
+            target,
+            index,
+            expr,
+        } => visit_place_assignment(
+            target,
+            PlaceStep::Index(index),
+            expr,
+            scope,
+            &mut module_builder,
+        ),
+        StmtAst::FieldAssignment {
+            target,
+            field,
+            expr,
+        } => visit_place_assignment(
+            target,
+            PlaceStep::Field(field),
+            expr,
+            scope,
+            &mut module_builder,
+        ),
+        StmtAst::Break(location) => {
+            if !module_builder.break_loop() {
+                return Err(GearsError::CompileError {
+                    location: location.clone(),
+                    message: "break is only allowed inside of a loop".to_string(),
+                });
+            }
 
-                match symbol {
-                    Some(e) => *e.get_index(),
-                    None => return Err(GearsError::SymbolNotFound(name.clone())),
+            Ok(Vec::new())
+        }
+        StmtAst::Continue(location) => {
+            if !module_builder.continue_loop() {
+                return Err(GearsError::CompileError {
+                    location: location.clone(),
+                    message: "continue is only allowed inside of a loop".to_string(),
+                });
+            }
+
+            Ok(Vec::new())
+        }
+        StmtAst::Return(expr, location) => {
+            let return_types = match scope.return_types() {
+                Some(e) => e.clone(),
+                None => {
+                    return Err(GearsError::CompileError {
+                        location: location.clone(),
+                        message: "return is only allowed inside of a function".to_string(),
+                    })
                 }
             };
 
-            module_builder.store_fast(index);
-            Ok(var_types)
+            let expr_types = match expr {
+                Some(expr) => visit_expr(expr, scope, &mut module_builder)?,
+                None => {
+                    module_builder.load_none();
+                    vec![Type::None]
+                }
+            };
+
+            if !types_assignable(&expr_types, &return_types) {
+                return Err(GearsError::TypeError(format!(
+                    "{:?} is not compatible with {:?}",
+                    return_types, expr_types
+                )));
+            }
+
+            module_builder.return_value();
+            Ok(Vec::new())
         }
     }
 }
@@ -169,47 +2163,199 @@ fn visit_expr(
     mut module_builder: &mut ModuleBuilder,
 ) -> Result<Types, GearsError> {
     let res: Types = match expr {
-        ExprAst::Integer(e) => {
-            module_builder.load_int(*e);
-            vec![Type::new_int()]
+        ExprAst::Integer(e, location) => {
+            if *e > i64::max_value() as u64 {
+                return Err(GearsError::ParseError {
+                    location: location.clone(),
+                    message: format!("Integer literal {} is out of range", e),
+                });
+            }
+
+            module_builder.load_int(*e as i64)?;
+            vec![Type::Int]
+        }
+        ExprAst::Float(e) => {
+            module_builder.load_float(*e)?;
+            vec![Type::Float]
         }
         ExprAst::Str(e) => {
-            module_builder.load_str((*e).to_string());
-            vec![Type::new_str()]
+            module_builder.load_str((*e).to_string())?;
+            vec![Type::Str]
         }
         ExprAst::Bool(b) => {
             module_builder.load_bool(b);
-            vec![Type::new_bool()]
+            vec![Type::Bool]
+        }
+        ExprAst::None => {
+            module_builder.load_none();
+            vec![Type::None]
+        }
+        ExprAst::Format(parts) => {
+            if parts.is_empty() {
+                module_builder.load_str(String::new())?;
+            }
+
+            // Each part is converted to a string and joined to the parts before it
+            for (index, part) in parts.iter().enumerate() {
+                if visit_expr(part, scope, &mut module_builder)? != vec![Type::Str] {
+                    module_builder.to_str();
+                }
+
+                if index > 0 {
+                    module_builder.op_add();
+                }
+            }
+
+            vec![Type::Str]
+        }
+        ExprAst::List(exprs) => {
+            if exprs.len() > u8::max_value() as usize {
+                return Err(GearsError::InternalCompilerError(format!(
+                    "List literals are limited to {} items",
+                    u8::max_value()
+                )));
+            }
+
+            let mut element_types = Vec::new();
+
+            for expr in exprs {
+                element_types.extend(visit_expr(expr, scope, &mut module_builder)?);
+            }
+
+            module_builder.build_list(exprs.len() as u8);
+            vec![Type::new_list(element_types)]
+        }
+        ExprAst::Tuple(exprs) => {
+            if exprs.len() > u8::max_value() as usize {
+                return Err(GearsError::InternalCompilerError(format!(
+                    "Tuple literals are limited to {} items",
+                    u8::max_value()
+                )));
+            }
+
+            let mut item_types = Vec::new();
+
+            for expr in exprs {
+                item_types.push(visit_expr(expr, scope, &mut module_builder)?);
+            }
+
+            module_builder.build_tuple(exprs.len() as u8);
+            vec![Type::new_tuple(item_types)]
+        }
+        ExprAst::Map(entries) => {
+            if entries.len() > u8::max_value() as usize {
+                return Err(GearsError::InternalCompilerError(format!(
+                    "Map literals are limited to {} entries",
+                    u8::max_value()
+                )));
+            }
+
+            let mut key_types = Vec::new();
+            let mut value_types = Vec::new();
+
+            for (key, value) in entries {
+                key_types.extend(visit_expr(key, scope, &mut module_builder)?);
+                value_types.extend(visit_expr(value, scope, &mut module_builder)?);
+            }
+
+            expect_hashable(&key_types)?;
+            module_builder.build_map(entries.len() as u8);
+            vec![Type::new_map(key_types, value_types)]
+        }
+        ExprAst::Index { expr, index } => {
+            let expr_types = visit_expr(expr, scope, &mut module_builder)?;
+            let index_types = visit_expr(index, scope, &mut module_builder)?;
+
+            module_builder.index();
+
+            // A literal index into a tuple knows exactly which item it returns
+            match (expr_types.as_slice(), index.as_ref()) {
+                ([t], ExprAst::Integer(i, _)) if t.tuple_items().is_some() => {
+                    let items = t.tuple_items().unwrap();
+
+                    match items.get(*i as usize) {
+                        Some(item_types) => item_types.clone(),
+                        None => {
+                            return Err(GearsError::TypeError(format!(
+                                "Index {} is out of range for {:?}",
+                                i, t
+                            )))
+                        }
+                    }
+                }
+                _ => indexed_types(&expr_types, &index_types)?,
+            }
+        }
+        ExprAst::Slice { expr, start, end } => {
+            let expr_types = visit_expr(expr, scope, &mut module_builder)?;
+
+            for bound in &[start, end] {
+                match bound {
+                    Some(bound) => {
+                        let bound_types = visit_expr(bound, scope, &mut module_builder)?;
+                        expect_int(&bound_types, "Slice bound")?;
+                    }
+                    None => module_builder.load_none(),
+                }
+            }
+
+            module_builder.slice();
+
+            for expr_type in &expr_types {
+                if expr_type.list_elements().is_none() && *expr_type != Type::Str {
+                    return Err(GearsError::TypeError(format!(
+                        "Only lists and strings can be sliced. Found: {:?}",
+                        expr_types
+                    )));
+                }
+            }
+
+            // A slice has the same type as the value it was taken from
+            expr_types
         }
-        ExprAst::List(_) => panic!("List is not added yet"),
         ExprAst::If {
             cmp_expr,
             exprs,
             else_exprs,
         } => {
+            let (then_narrowing, else_narrowing) = condition_narrowing(cmp_expr, scope);
             visit_expr(cmp_expr, scope, &mut module_builder)?;
             let jump_index = module_builder.start_jump_if_false();
-            let if_block_types =
-                HashSet::from_iter(visit_block(exprs, scope, &mut module_builder)?);
+            let if_block_types: HashSet<Type> = {
+                let mut then_scope = (&scope).push();
+                apply_narrowing(&mut then_scope, &then_narrowing);
+                HashSet::from_iter(visit_block(exprs, &mut then_scope, &mut module_builder)?)
+            };
 
             let else_block_types: HashSet<Type> = match else_exprs {
                 Some(exprs) => {
-                    let jump_index = module_builder.start_else(jump_index);
-                    let mut last_type = visit_block(exprs, scope, &mut module_builder)?;
+                    let jump_index = module_builder.start_else(jump_index)?;
+                    let mut else_scope = (&scope).push();
+                    apply_narrowing(&mut else_scope, &else_narrowing);
+                    let last_type = visit_block(exprs, &mut else_scope, &mut module_builder)?;
 
-                    module_builder.end_jump(jump_index);
+                    module_builder.end_jump(jump_index)?;
                     HashSet::from_iter(last_type)
                 }
                 None => {
-                    let jump_index = module_builder.start_else(jump_index);
+                    let jump_index = module_builder.start_else(jump_index)?;
                     module_builder.load_none();
-                    module_builder.end_jump(jump_index);
+                    module_builder.end_jump(jump_index)?;
                     let mut res = HashSet::new();
-                    res.insert(Type::new_none());
+                    res.insert(Type::None);
                     res
                 }
             };
 
+            // Code after the if can only be reached through a branch that finished
+            if if_block_types.is_empty() {
+                apply_narrowing(scope, &else_narrowing);
+            } else if else_block_types.is_empty() {
+                apply_narrowing(scope, &then_narrowing);
+            }
+
+            widen_assigned(expr, scope);
+
             let mut res = Vec::new();
             for res_type in if_block_types.union(&else_block_types) {
                 res.push(res_type.clone());
@@ -218,36 +2364,207 @@ fn visit_expr(
             res
         }
         ExprAst::While { cmp_expr, exprs } => {
+            widen_assigned(expr, scope);
             let loop_index = module_builder.start_loop_check();
             visit_expr(cmp_expr, scope, &mut module_builder)?;
             let jump_index = module_builder.start_jump_if_false();
-            let result = visit_block(exprs, scope, &mut module_builder)?;
-            module_builder.end_loop(loop_index, jump_index);
-            result
+            visit_block(exprs, scope, &mut module_builder)?;
+            module_builder.pop_top();
+            module_builder.start_continue()?;
+            module_builder.end_loop(loop_index, jump_index)?;
+            module_builder.load_none();
+            vec![Type::None]
         }
-        ExprAst::For { name, range, exprs } => {
-            // Push a loop level scope and define the name into that scope
+        ExprAst::For {
+            binding,
+            range,
+            exprs,
+        } => {
+            expect_unique_names(binding)?;
+            widen_assigned(expr, scope);
+
+            // Push a loop level scope and define the names into that scope
             let mut local_scope = (&scope).push();
-            let name_index = local_scope.def_variable(name.clone(), vec![Type::from("int")]);
-            module_builder.load_int(range.start());
-            module_builder.store_fast(name_index);
-            let loop_index = module_builder.start_loop_check();
 
-            let cmp_expr = &ExprAst::new_op(
-                ExprAst::Variable(name.clone()),
-                BinOpAst::LessThan,
-                ExprAst::Integer(range.end()),
-            );
+            match range {
+                RangeAst::Range { start, end, step } => {
+                    let name = match binding {
+                        BindingAst::Name(name) => name,
+                        BindingAst::Tuple(_) => {
+                            return Err(GearsError::TypeError(
+                                "A range can only be bound to a single name".to_string(),
+                            ))
+                        }
+                    };
+
+                    let start_types = visit_expr(start, &mut local_scope, &mut module_builder)?;
+                    expect_int(&start_types, "Range start")?;
+                    let name_index = local_scope.def_variable(name.clone(), vec![Type::Int]);
+                    module_builder.store_fast(name_index);
 
-            visit_expr(cmp_expr, &mut local_scope, &mut module_builder)?;
+                    let end_types = visit_expr(end, &mut local_scope, &mut module_builder)?;
+                    expect_int(&end_types, "Range end")?;
+                    let end_index = local_scope.def_variable("@end".to_string(), end_types);
+                    module_builder.store_fast(end_index);
 
-            let jump_index = module_builder.start_jump_if_false();
-            let result = visit_block(exprs, &mut local_scope, &mut module_builder)?;
-            module_builder.load_fast(name_index);
-            module_builder.inc_one();
-            module_builder.store_fast(name_index);
-            module_builder.end_loop(loop_index, jump_index);
-            result
+                    let step_types = match step {
+                        Some(step) => visit_expr(step, &mut local_scope, &mut module_builder)?,
+                        None => {
+                            module_builder.load_fast(name_index);
+                            module_builder.load_fast(end_index);
+                            module_builder.range_step();
+                            vec![Type::Int]
+                        }
+                    };
+                    expect_int(&step_types, "Range step")?;
+                    let step_index = local_scope.def_variable("@step".to_string(), step_types);
+                    module_builder.store_fast(step_index);
+
+                    let loop_index = module_builder.start_loop_check();
+                    module_builder.load_fast(name_index);
+                    module_builder.load_fast(end_index);
+                    module_builder.load_fast(step_index);
+                    module_builder.range_check();
+
+                    let jump_index = module_builder.start_jump_if_false();
+                    visit_block(exprs, &mut local_scope, &mut module_builder)?;
+                    module_builder.pop_top();
+                    module_builder.start_continue()?;
+                    module_builder.load_fast(name_index);
+                    module_builder.load_fast(step_index);
+                    module_builder.range_next();
+                    module_builder.store_fast(name_index);
+                    module_builder.end_loop(loop_index, jump_index)?;
+                }
+                RangeAst::Iter(expr) => {
+                    let expr_types = visit_expr(expr, &mut local_scope, &mut module_builder)?;
+                    let item_types = item_types(&expr_types, "iterated over")?;
+
+                    // The hidden names can not clash as they are not valid identifiers
+                    module_builder.get_iter();
+                    let iter_index = local_scope.def_variable(
+                        "@iter".to_string(),
+                        vec![Type::new_list(item_types.clone())],
+                    );
+                    module_builder.store_fast(iter_index);
+                    module_builder.load_int(0)?;
+                    let counter_index =
+                        local_scope.def_variable("@counter".to_string(), vec![Type::Int]);
+                    module_builder.store_fast(counter_index);
+
+                    let loop_index = module_builder.start_loop_check();
+                    module_builder.load_fast(counter_index);
+                    module_builder.load_fast(iter_index);
+                    module_builder.len();
+                    module_builder.op_less();
+
+                    let jump_index = module_builder.start_jump_if_false();
+                    module_builder.load_fast(iter_index);
+                    module_builder.load_fast(counter_index);
+                    module_builder.index();
+                    visit_binding(
+                        binding,
+                        &item_types,
+                        true,
+                        true,
+                        &mut local_scope,
+                        &mut module_builder,
+                    )?;
+                    visit_block(exprs, &mut local_scope, &mut module_builder)?;
+                    module_builder.pop_top();
+                    module_builder.start_continue()?;
+                    module_builder.load_fast(counter_index);
+                    module_builder.inc_one();
+                    module_builder.store_fast(counter_index);
+                    module_builder.end_loop(loop_index, jump_index)?;
+                }
+            }
+
+            module_builder.load_none();
+            vec![Type::None]
+        }
+        ExprAst::Op(left, BinOpAst::And, right) => {
+            // Both sides jump to the false branch as soon as one is falsy
+            visit_expr(left, scope, &mut module_builder)?;
+            let left_jump = module_builder.start_jump_if_false();
+            let mut right_scope = (&scope).push();
+            apply_narrowing(&mut right_scope, &condition_narrowing(left, scope).0);
+            visit_expr(right, &mut right_scope, &mut module_builder)?;
+            let right_jump = module_builder.start_jump_if_false();
+            module_builder.load_bool(&true);
+            let end_jump = module_builder.start_jump();
+            module_builder.end_jump(left_jump)?;
+            module_builder.end_jump(right_jump)?;
+            module_builder.load_bool(&false);
+            module_builder.end_jump(end_jump)?;
+
+            vec![Type::Bool]
+        }
+        ExprAst::Op(left, BinOpAst::Or, right) => {
+            // Both sides jump to the true branch as soon as one is truthy
+            visit_expr(left, scope, &mut module_builder)?;
+            let left_jump = module_builder.start_jump_if_true();
+            let mut right_scope = (&scope).push();
+            apply_narrowing(&mut right_scope, &condition_narrowing(left, scope).1);
+            visit_expr(right, &mut right_scope, &mut module_builder)?;
+            let right_jump = module_builder.start_jump_if_true();
+            module_builder.load_bool(&false);
+            let end_jump = module_builder.start_jump();
+            module_builder.end_jump(left_jump)?;
+            module_builder.end_jump(right_jump)?;
+            module_builder.load_bool(&true);
+            module_builder.end_jump(end_jump)?;
+
+            vec![Type::Bool]
+        }
+        ExprAst::UnaryOp(UnaryOpAst::Neg, expr) => match **expr {
+            // Fold negative literals so the full range of an int can be written
+            ExprAst::Integer(e, _) if e <= i64::max_value() as u64 + 1 => {
+                module_builder.load_int((e as i64).wrapping_neg())?;
+                vec![Type::Int]
+            }
+            ExprAst::Float(e) => {
+                module_builder.load_float(-e)?;
+                vec![Type::Float]
+            }
+            _ => {
+                let expr_types = visit_expr(expr, scope, &mut module_builder)?;
+
+                if !is_numeric(&expr_types) {
+                    return Err(GearsError::TypeError(format!(
+                        "Only ints and floats can be negated. Found: {:?}",
+                        expr_types
+                    )));
+                }
+
+                module_builder.op_neg();
+                expr_types
+            }
+        },
+        ExprAst::UnaryOp(op, expr) => {
+            let expr_types = visit_expr(expr, scope, &mut module_builder)?;
+
+            match op {
+                UnaryOpAst::Not => {
+                    module_builder.op_not();
+                    vec![Type::Bool]
+                }
+                UnaryOpAst::Plus => {
+                    if !is_numeric(&expr_types) {
+                        return Err(GearsError::TypeError(format!(
+                            "Unary plus is only supported on ints and floats. Found: {:?}",
+                            expr_types
+                        )));
+                    }
+
+                    expr_types
+                }
+                UnaryOpAst::Neg => {
+                    return Err(GearsError::InternalCompilerError(
+                        "Negation must be compiled with folding".to_string(),
+                    ))
+                }
+            }
         }
         ExprAst::Op(left, op, right) => {
             use self::BinOpAst::*;
@@ -260,85 +2577,259 @@ fn visit_expr(
                 BinOpAst::Sub => module_builder.op_sub(),
                 BinOpAst::Mul => module_builder.op_mul(),
                 BinOpAst::Div => module_builder.op_div(),
+                BinOpAst::FloorDiv => module_builder.op_floor_div(),
+                BinOpAst::Mod => module_builder.op_mod(),
+                BinOpAst::Pow => module_builder.op_pow(),
                 BinOpAst::EqEq => module_builder.op_eqeq(),
                 BinOpAst::NotEq => module_builder.op_not_eq(),
                 BinOpAst::LessThan => module_builder.op_less(),
                 BinOpAst::LessThanEq => module_builder.op_less_eq(),
                 BinOpAst::GreaterThan => module_builder.op_greater(),
                 BinOpAst::GreaterThanEq => module_builder.op_greater_eq(),
+                BinOpAst::In => module_builder.op_in(),
+                BinOpAst::And | BinOpAst::Or => {
+                    return Err(GearsError::InternalCompilerError(format!(
+                        "{:?} must be compiled with short circuiting",
+                        op
+                    )))
+                }
             }
 
             match op {
-                Add | Sub | Mul | Div => {
-                    if left_types.len() != 1 || left_types[0] != Type::new_int() {
+                Add | Sub | Mul | Div | FloorDiv | Mod | Pow => {
+                    if !is_numeric(&left_types) {
                         return Err(GearsError::TypeError(format!(
-                            "Only ints are supported for math at this time. Left hand is: {:?}",
+                            "Only ints and floats are supported for math at this time. Left hand is: {:?}",
                             left_types
                         )));
                     }
-                    if right_types.len() != 1 || right_types[0] != Type::new_int() {
+                    if !is_numeric(&right_types) {
                         return Err(GearsError::TypeError(format!(
-                            "Only ints are supported for math at this time. Right hand is: {:?}",
+                            "Only ints and floats are supported for math at this time. Right hand is: {:?}",
                             right_types
                         )));
                     }
 
-                    vec![Type::new_int()]
+                    numeric_result(&left_types, &right_types)
                 }
-                _ => vec![Type::new_bool()],
+                In => {
+                    let item_types = item_types(&right_types, "searched")?;
+
+                    if !types_assignable(&left_types, &item_types) {
+                        return Err(GearsError::TypeError(format!(
+                            "{:?} can never be in {:?}",
+                            left_types, right_types
+                        )));
+                    }
+
+                    vec![Type::Bool]
+                }
+                _ => vec![Type::Bool],
             }
         }
-        ExprAst::FunctionCall { ref name, ref args } => {
-            for arg in args {
-                visit_expr(arg, scope, &mut module_builder)?;
-            }
-
-            let (maybe_symbol, is_global) = scope.resolve(name);
-
-            match maybe_symbol {
-                Some(symbol) => match symbol.get_type() {
-                    &SymbolType::Function {
-                        ref return_types, ..
-                    } => {
-                        if is_global {
-                            module_builder.call_fn(*symbol.get_index(), args.len() as u8);
-                            return_types.clone()
-                        } else {
-                            return Err(GearsError::InternalCompilerError(
-                                "Closures are not supported yet".to_string(),
-                            ));
-                        }
-                    }
-                    &SymbolType::Variable { .. } => {
-                        // TODO: return location
-                        return Err(GearsError::ParseError {
-                            location: lexer::Span::new(0, 0),
-                            message: format!("{} is not callable", name),
-                        });
-                    }
-                },
-                None => return Err(GearsError::SymbolNotFound(name.clone())),
+        ExprAst::Match {
+            expr: subject_expr,
+            arms,
+        } => {
+            let expr_types = visit_expr(subject_expr, scope, &mut module_builder)?;
+
+            // The value is kept in a hidden slot so each arm can test it
+            let mut match_scope = (&scope).push();
+            let match_index = match_scope.def_variable("@match".to_string(), expr_types.clone());
+            module_builder.store_fast(match_index);
+
+            // Types that are fully matched by an arm are left out of later arms
+            let mut remaining = expr_types.clone();
+            let mut patterns = Vec::new();
+            let mut arm_types = Vec::new();
+
+            for (pattern, _) in arms {
+                if remaining.is_empty() {
+                    return Err(GearsError::TypeError(
+                        "This arm can never match, every value is matched by the arms above"
+                            .to_string(),
+                    ));
+                }
+
+                let mut pattern = lower_pattern(pattern, &remaining, &match_scope)?;
+                narrow_wildcards(&mut pattern, &patterns, &match_scope)?;
+                arm_types.push(pattern.narrowed_types(&remaining));
+                patterns.push(pattern);
+
+                let rows: Vec<Vec<Option<&Pattern>>> =
+                    patterns.iter().map(|p| vec![p.refutable()]).collect();
+                remaining.retain(|t| !is_exhaustive(rows.clone(), &[vec![t.clone()]], &match_scope));
+            }
+
+            if !remaining.is_empty() {
+                return Err(GearsError::TypeError(format!(
+                    "Match does not cover every value of {:?}",
+                    expr_types
+                )));
+            }
+
+            // Matching on a variable narrows it inside each arm
+            let subject = match **subject_expr {
+                ExprAst::Variable(ref name) => Some(name),
+                _ => None,
+            };
+            let mut end_jumps = Vec::new();
+            let mut res = Vec::new();
+
+            for ((pattern, (_, exprs)), types) in patterns.iter().zip(arms).zip(arm_types) {
+                let mut fail_jumps = Vec::new();
+                visit_pattern_test(
+                    pattern,
+                    match_index,
+                    &mut Vec::new(),
+                    &mut match_scope,
+                    &mut module_builder,
+                    &mut fail_jumps,
+                )?;
+
+                let mut arm_scope = (&match_scope).push();
+
+                if let Some(name) = subject {
+                    arm_scope.narrow(name, types);
+                }
+
+                visit_pattern_bindings(
+                    pattern,
+                    match_index,
+                    &mut Vec::new(),
+                    &mut arm_scope,
+                    &mut module_builder,
+                );
+                res.extend(visit_block(exprs, &mut arm_scope, &mut module_builder)?);
+                end_jumps.push(module_builder.start_jump());
+
+                // A failed test moves on to the next arm
+                for jump in fail_jumps {
+                    module_builder.end_jump(jump)?;
+                }
+            }
+
+            // Only reached when no arm matched, which the exhaustive check rules out
+            module_builder.load_none();
+
+            for jump in end_jumps {
+                module_builder.end_jump(jump)?;
             }
+
+            widen_assigned(expr, scope);
+            normalize_types(res)
         }
-        ExprAst::Variable(name) => {
-            let (maybe_symbol, _) = scope.resolve(name);
+        ExprAst::Field { expr, field } => match resolve_qualified(scope, expr, field)? {
+            Some(symbol) => visit_symbol(field, &symbol, true, &mut module_builder)?,
+            None => {
+                let expr_types = visit_expr(expr, scope, &mut module_builder)?;
 
-            match maybe_symbol {
-                Some(symbol) => match symbol.get_type() {
-                    &SymbolType::Function { .. } => {
-                        return Err(GearsError::InternalCompilerError(
-                            "Functions are not first class yet".to_string(),
-                        ))
-                    }
-                    &SymbolType::Variable { ref types } => {
-                        module_builder.load_fast(*symbol.get_index());
-                        types.clone()
+                module_builder.get_field(field.clone())?;
+                field_types(scope, &expr_types, field)?
+            }
+        },
+        ExprAst::Call { expr, args, kwargs } => {
+            let qualified = match expr.as_ref() {
+                ExprAst::Field {
+                    expr: target,
+                    field,
+                } => resolve_qualified(scope, target, field)?.map(|symbol| (field, symbol)),
+                _ => None,
+            };
+
+            match qualified {
+                Some((name, symbol)) => {
+                    visit_call(name, &symbol, true, args, kwargs, scope, &mut module_builder)?
+                }
+                None => {
+                    let types = visit_expr(expr, scope, &mut module_builder)?;
+                    visit_indirect_call(&types, args, kwargs, scope, &mut module_builder)?
+                }
+            }
+        }
+        ExprAst::Function {
+            args,
+            return_type,
+            exprs,
+        } => {
+            let return_types = resolve_types(return_type, scope)?;
+            let mut arg_types = Vec::new();
+
+            for arg in args {
+                if arg.default().is_some() {
+                    return Err(GearsError::TypeError(format!(
+                        "{} can not have a default value, only named functions have defaults",
+                        arg.name()
+                    )));
+                }
+
+                arg_types.push(resolve_types(arg.arg_types(), scope)?);
+            }
+
+            // Locals of the enclosing function that the body uses are copied
+            // into the function value and stored after its args
+            let mut names = Vec::new();
+            collect_stmts_names(exprs, &mut names);
+            names.sort();
+            names.dedup();
+
+            let mut captures = Vec::new();
+
+            for name in names {
+                if args.iter().any(|arg| *arg.name() == name) {
+                    continue;
+                }
+
+                if let (Some(symbol), false) = scope.resolve(&name) {
+                    if let SymbolType::Variable { types, .. } = symbol.get_type() {
+                        captures.push((name.clone(), *symbol.get_index(), types.clone()));
                     }
-                },
-                None => return Err(GearsError::SymbolNotFound(name.clone())),
+                }
+            }
+
+            module_builder.start_function_literal(args.len());
+            let fn_index = {
+                let mut fn_scope = scope.global().push_function(return_types.clone());
+
+                for (arg, types) in args.iter().zip(&arg_types) {
+                    fn_scope.def_variable(arg.name().clone(), types.clone());
+                }
+
+                // The body has its own copy of a captured value, assigning to it
+                // would not change the variable it was captured from
+                for (name, _, types) in &captures {
+                    fn_scope.def_const(name.clone(), types.clone());
+                }
+
+                visit_function_body(exprs, &return_types, &mut fn_scope, &mut module_builder)?;
+                module_builder.finish_function()
+            };
+
+            for (_, index, _) in &captures {
+                Slot::new(*index, false).load(module_builder)?;
             }
+
+            module_builder.load_function(fn_index, captures.len() as u8)?;
+            vec![Type::new_function(arg_types, return_types)]
+        }
+        ExprAst::FunctionCall {
+            ref name,
+            ref args,
+            ref kwargs,
+        } => {
+            let (symbol, is_global) = match scope.resolve(name) {
+                (Some(symbol), is_global) => (symbol.clone(), is_global),
+                (None, _) => return Err(GearsError::SymbolNotFound(name.clone())),
+            };
+
+            visit_call(name, &symbol, is_global, args, kwargs, scope, &mut module_builder)?
         }
+        ExprAst::Variable(name) => match scope.resolve(name) {
+            (Some(symbol), is_global) => visit_symbol(name, symbol, is_global, &mut module_builder)?,
+            (None, _) => return Err(GearsError::SymbolNotFound(name.clone())),
+        },
     };
 
     Ok(res)
 }
+
@@ -6,6 +6,11 @@ use std::io;
 pub enum InterOpErrorType {
     TooManyArgs,
     TooFewArgs,
+    ConstGlobal,
+    WrongGlobalType,
+    UnknownArg,
+    DuplicateArg,
+    WrongArgType,
 }
 
 #[derive(Debug)]
@@ -14,7 +19,15 @@ pub enum GearsError {
     FunctionNotFound(String),
     InternalCompilerError(String),
     TypeError(String),
+    ArithmeticError(String),
+    IndexError(String),
+    KeyError(String),
     SymbolNotFound(String),
+    ModuleNotFound(String),
+    ImportError(String),
+    LimitError(String),
+    /// A global was read before the module initializer set it
+    UninitializedGlobal(String),
     InterOpError {
         error: InterOpErrorType,
         message: String,
@@ -23,6 +36,11 @@ pub enum GearsError {
         location: Span,
         message: String,
     },
+    /// Code that parses but is not valid where it is, e.g. a `break` outside of a loop
+    CompileError {
+        location: Span,
+        message: String,
+    },
 }
 
 impl From<io::Error> for GearsError {
@@ -59,8 +77,14 @@ impl<'a> From<ParseError<Span, Token, LexicalError>> for GearsError {
                 location: token.0,
             },
             ParseError::User { error } => GearsError::ParseError {
-                location: Span::new(0, 0),
-                message: format!("{:?}", error),
+                location: match error {
+                    LexicalError::IntegerOverflow(_, ref location)
+                    | LexicalError::FloatOverflow(_, ref location)
+                    | LexicalError::InvalidAssignmentTarget(ref location)
+                    | LexicalError::PositionalAfterKeyword(ref location) => location.clone(),
+                    _ => Span::new(0, 0),
+                },
+                message: format!("{}", error),
             },
         }
     }
@@ -83,6 +107,38 @@ impl PartialEq for GearsError {
                 TypeError(r) => l == r,
                 _ => false,
             },
+            ArithmeticError(l) => match other {
+                ArithmeticError(r) => l == r,
+                _ => false,
+            },
+            IndexError(l) => match other {
+                IndexError(r) => l == r,
+                _ => false,
+            },
+            KeyError(l) => match other {
+                KeyError(r) => l == r,
+                _ => false,
+            },
+            SymbolNotFound(l) => match other {
+                SymbolNotFound(r) => l == r,
+                _ => false,
+            },
+            ModuleNotFound(l) => match other {
+                ModuleNotFound(r) => l == r,
+                _ => false,
+            },
+            ImportError(l) => match other {
+                ImportError(r) => l == r,
+                _ => false,
+            },
+            LimitError(l) => match other {
+                LimitError(r) => l == r,
+                _ => false,
+            },
+            UninitializedGlobal(l) => match other {
+                UninitializedGlobal(r) => l == r,
+                _ => false,
+            },
             _ => false,
         }
     }
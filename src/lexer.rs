@@ -1,9 +1,22 @@
 use std::fmt;
-use std::str::FromStr;
+use std::str::{Chars, FromStr};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum LexicalError {
     UnknownToken(char),
+    /// The literal and where it starts
+    IntegerOverflow(String, Span),
+    /// A float literal too large for an f64, with where it starts
+    FloatOverflow(String, Span),
+    /// Only names and the fields and indexes of them can be assigned to, with where the
+    /// target starts
+    InvalidAssignmentTarget(Span),
+    /// Args given by position have to come before args given by name, with
+    /// where the args of the call start
+    PositionalAfterKeyword(Span),
+    /// A backslash in a string that is not followed by a known escape
+    InvalidEscape(String),
+    UnterminatedString,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Default)]
@@ -35,7 +48,31 @@ impl fmt::Display for Span {
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Clone)]
+impl fmt::Display for LexicalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LexicalError::UnknownToken(c) => write!(f, "Unknown character {:?}", c),
+            LexicalError::IntegerOverflow(literal, location) => {
+                write!(f, "Integer literal {} is out of range at {}", literal, location)
+            }
+            LexicalError::FloatOverflow(literal, location) => {
+                write!(f, "Float literal {} is out of range at {}", literal, location)
+            }
+            LexicalError::InvalidAssignmentTarget(location) => {
+                write!(f, "Invalid assignment target at {}", location)
+            }
+            LexicalError::PositionalAfterKeyword(location) => write!(
+                f,
+                "Args given by position have to come before args given by name at {}",
+                location
+            ),
+            LexicalError::InvalidEscape(escape) => write!(f, "Invalid escape {} in a string", escape),
+            LexicalError::UnterminatedString => write!(f, "Unterminated string"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub enum Token {
     // No Data
     Comma,
@@ -46,9 +83,12 @@ pub enum Token {
     LBracket,
     RBracket,
     Pipe,
+    Question,
     Arrow,
+    FatArrow,
     SemiColon,
     Colon,
+    Dot,
     Eq,
     EqEq,
     NotEq,
@@ -59,35 +99,60 @@ pub enum Token {
     Plus,
     Minus,
     Star,
+    StarStar,
     Slash,
+    SlashSlash,
+    Percent,
 
     // Data
     Name(String),
-    Integer(i64),
+    // Integers are unsigned here, the sign is applied by the compiler
+    Integer(u64),
+    Float(f64),
     Str(String),
+    /// `f"` and the closing quote of a format string, the parts are in between
+    FormatStart,
+    FormatEnd,
 
     // Keywords
     Def,
     Let,
+    Const,
+    Import,
+    From,
     If,
     Else,
     True,
     False,
+    None,
     While,
     For,
     In,
     To,
-    List,
+    Step,
+    Break,
+    Continue,
+    Return,
+    Struct,
+    Enum,
+    Match,
+    And,
+    Or,
+    Not,
 }
 
 pub type Spanned<Token, Loc, Error> = Result<(Loc, Token, Loc), Error>;
+pub type Tokens = Vec<Spanned<Token, Span, LexicalError>>;
 
-pub fn lex(input: &str) -> Vec<Spanned<Token, Span, LexicalError>> {
+pub fn lex(input: &str) -> Tokens {
+    lex_from(input, 1, 1)
+}
+
+/// Lexes input that starts at the line and column, used for format strings
+fn lex_from(input: &str, mut line: usize, mut column: usize) -> Tokens {
     let mut tokens = Vec::new();
     let mut chars = input.chars();
     let mut lookahead = chars.next();
-    let mut line = 1;
-    let mut column = 1;
 
     macro_rules! token_data {
         ($tok:expr, $size: expr) => {{
@@ -117,15 +182,18 @@ pub fn lex(input: &str) -> Vec<Spanned<Token, Span, LexicalError>> {
             '[' => token!(LBrace, 1),
             ']' => token!(RBrace, 1),
             '|' => token!(Pipe, 1),
+            '?' => token!(Question, 1),
             '{' => token!(LBracket, 1),
             '}' => token!(RBracket, 1),
             ';' => token!(SemiColon, 1),
             ':' => token!(Colon, 1),
+            '.' => token!(Dot, 1),
             '=' => {
                 lookahead = chars.next();
                 if let Some(c) = lookahead {
                     match c {
                         '=' => token!(EqEq, 2),
+                        '>' => token!(FatArrow, 2),
                         _ => {
                             token!(Eq, 1);
                             continue;
@@ -189,8 +257,35 @@ pub fn lex(input: &str) -> Vec<Spanned<Token, Span, LexicalError>> {
                     token!(Minus, 1)
                 }
             }
-            '*' => token!(Star, 1),
-            '/' => token!(Slash, 1),
+            '*' => {
+                lookahead = chars.next();
+                if let Some(c) = lookahead {
+                    match c {
+                        '*' => token!(StarStar, 2),
+                        _ => {
+                            token!(Star, 1);
+                            continue;
+                        }
+                    }
+                } else {
+                    token!(Star, 1)
+                }
+            }
+            '/' => {
+                lookahead = chars.next();
+                if let Some(c) = lookahead {
+                    match c {
+                        '/' => token!(SlashSlash, 2),
+                        _ => {
+                            token!(Slash, 1);
+                            continue;
+                        }
+                    }
+                } else {
+                    token!(Slash, 1)
+                }
+            }
+            '%' => token!(Percent, 1),
 
             _ if c.is_alphabetic() || c == '_' => {
                 let (tmp, next) = take_while(c, &mut chars, |c| {
@@ -199,49 +294,106 @@ pub fn lex(input: &str) -> Vec<Spanned<Token, Span, LexicalError>> {
                 lookahead = next;
                 let len = tmp.len();
 
+                // An f right before a quote starts a format string
+                if tmp == "f" && (next == Some('"') || next == Some('\'')) {
+                    match take_format(next.unwrap(), &mut chars, line, column) {
+                        Ok((format_tokens, format_len)) => {
+                            tokens.extend(format_tokens);
+                            column += format_len;
+                        }
+                        Err(e) => tokens.push(Err(e)),
+                    }
+
+                    lookahead = chars.next();
+                    continue;
+                }
+
                 match tmp.as_str() {
                     "def" => token!(Def, len),
                     "let" => token!(Let, len),
+                    "const" => token!(Const, len),
+                    "import" => token!(Import, len),
+                    "from" => token!(From, len),
                     "if" => token!(If, len),
                     "else" => token!(Else, len),
                     "true" => token!(True, len),
                     "false" => token!(False, len),
+                    "none" => token!(None, len),
                     "while" => token!(While, len),
                     "for" => token!(For, len),
                     "in" => token!(In, len),
                     "to" => token!(To, len),
-                    "list" => token!(List, len),
+                    "step" => token!(Step, len),
+                    "break" => token!(Break, len),
+                    "continue" => token!(Continue, len),
+                    "return" => token!(Return, len),
+                    "struct" => token!(Struct, len),
+                    "enum" => token!(Enum, len),
+                    "match" => token!(Match, len),
+                    "and" => token!(And, len),
+                    "or" => token!(Or, len),
+                    "not" => token!(Not, len),
                     _ => token_data!(Token::Name(tmp), len),
                 }
 
                 continue;
             }
 
-            '"' => {
-                if let Some(c) = chars.next() {
-                    let (tmp, _) = take_while_lookback(c, &mut chars, |c, l| c != '"' && l != '\\');
-                    token_data!(Token::Str(tmp), tmp.len());
-                // Not continueing will eat the ending "
-                } else {
-                    tokens.push(Err(LexicalError::UnknownToken(c)))
+            '"' | '\'' => match take_string(c, &mut chars) {
+                Ok((tmp, len)) => token_data!(Token::Str(tmp), len),
+                Err(e) => tokens.push(Err(e)),
+            },
+
+            _ if c.is_digit(10) => {
+                let (mut tmp, mut next) = take_while(c, &mut chars, |c| c.is_digit(10));
+                let mut is_float = false;
+
+                // A dot only starts a fraction if a digit follows it
+                if next == Some('.') && peek_digit(&chars, false) {
+                    let (frac, frac_next) = take_while('.', &mut chars, |c| c.is_digit(10));
+                    tmp += &frac;
+                    next = frac_next;
+                    is_float = true;
                 }
-            }
 
-            '\'' => {
-                if let Some(c) = chars.next() {
-                    let (tmp, _) =
-                        take_while_lookback(c, &mut chars, |c, l| c != '\'' && l != '\\');
-                    token_data!(Token::Str(tmp), tmp.len());
-                // Not continueing will eat the ending "
-                } else {
-                    tokens.push(Err(LexicalError::UnknownToken(c)))
+                if (next == Some('e') || next == Some('E')) && peek_digit(&chars, true) {
+                    tmp.push('e');
+                    let mut exp_start = chars.next().unwrap();
+
+                    if exp_start == '+' || exp_start == '-' {
+                        tmp.push(exp_start);
+                        exp_start = chars.next().unwrap();
+                    }
+
+                    let (exp, exp_next) = take_while(exp_start, &mut chars, |c| c.is_digit(10));
+                    tmp += &exp;
+                    next = exp_next;
+                    is_float = true;
                 }
-            }
 
-            _ if c.is_digit(10) => {
-                let (tmp, next) = take_while(c, &mut chars, |c| c.is_digit(10));
                 lookahead = next;
-                token_data!(Token::Integer(i64::from_str(&tmp).unwrap()), tmp.len());
+
+                if is_float {
+                    match f64::from_str(&tmp) {
+                        Ok(number) if number.is_finite() => {
+                            token_data!(Token::Float(number), tmp.len())
+                        }
+                        _ => {
+                            let location = Span::new(line, column);
+                            column += tmp.len();
+                            tokens.push(Err(LexicalError::FloatOverflow(tmp, location)));
+                        }
+                    }
+                } else {
+                    match u64::from_str(&tmp) {
+                        Ok(number) => token_data!(Token::Integer(number), tmp.len()),
+                        Err(_) => {
+                            let location = Span::new(line, column);
+                            column += tmp.len();
+                            tokens.push(Err(LexicalError::IntegerOverflow(tmp, location)));
+                        }
+                    }
+                }
                 continue;
             }
 
@@ -285,26 +437,192 @@ where
     return (buf, None);
 }
 
-fn take_while_lookback<C, F>(c0: char, chars: &mut C, f: F) -> (String, Option<char>)
-where
-    C: Iterator<Item = char>,
-    F: Fn(char, char) -> bool,
-{
+/// Checks if the next character is a digit without consuming it
+///
+/// If `signed` is true, a leading `+` or `-` is skipped over first
+fn peek_digit(chars: &Chars, signed: bool) -> bool {
+    let mut peek = chars.clone();
+
+    match peek.next() {
+        Some(c) if c.is_digit(10) => true,
+        Some(c) if signed && (c == '+' || c == '-') => match peek.next() {
+            Some(c) => c.is_digit(10),
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+/// Reads a string up to the closing quote, decoding escapes
+///
+/// Returns the string and the number of characters read, including both quotes.
+fn take_string(quote: char, chars: &mut Chars) -> Result<(String, usize), LexicalError> {
     let mut buf = String::new();
-    let mut l = c0;
+    let mut len = 1;
 
-    buf.push(c0);
+    loop {
+        let c = chars.next().ok_or(LexicalError::UnterminatedString)?;
+        len += 1;
+
+        match c {
+            '\\' => {
+                let (escaped, escape_len) = take_escape(chars)?;
+                buf.push(escaped);
+                len += escape_len;
+            }
+            _ if c == quote => return Ok((buf, len)),
+            _ => buf.push(c),
+        }
+    }
+}
+
+/// Decodes the escape after a backslash, returning it and the characters read
+fn take_escape(chars: &mut Chars) -> Result<(char, usize), LexicalError> {
+    match chars.next() {
+        Some('n') => Ok(('\n', 1)),
+        Some('t') => Ok(('\t', 1)),
+        Some('r') => Ok(('\r', 1)),
+        Some('0') => Ok(('\0', 1)),
+        Some('\\') => Ok(('\\', 1)),
+        Some('"') => Ok(('"', 1)),
+        Some('\'') => Ok(('\'', 1)),
+        Some('u') => {
+            let mut hex = String::new();
+
+            if chars.next() != Some('{') {
+                return Err(LexicalError::InvalidEscape("\\u".to_string()));
+            }
+
+            loop {
+                match chars.next() {
+                    Some('}') => break,
+                    Some(c) if c.is_ascii_hexdigit() && hex.len() < 6 => hex.push(c),
+                    _ => return Err(LexicalError::InvalidEscape(format!("\\u{{{}", hex))),
+                }
+            }
+
+            match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                Some(c) => Ok((c, hex.len() + 3)),
+                None => Err(LexicalError::InvalidEscape(format!("\\u{{{}}}", hex))),
+            }
+        }
+        Some(c) => Err(LexicalError::InvalidEscape(format!("\\{}", c))),
+        None => Err(LexicalError::UnterminatedString),
+    }
+}
+
+/// Lexes a format string after its `f`, returning its tokens and its length
+///
+/// Text is emitted as `Str` tokens and each `{expr}` as the tokens of the
+/// expression wrapped in braces. `{{` and `}}` are literal braces.
+fn take_format(
+    quote: char,
+    chars: &mut Chars,
+    line: usize,
+    column: usize,
+) -> Result<(Tokens, usize), LexicalError> {
+    let mut tokens = vec![Ok((
+        Span::new(line, column),
+        Token::FormatStart,
+        Span::new(line, column + 2),
+    ))];
+    let mut part = String::new();
+    // The f and the opening quote are already read
+    let mut len = 2;
+
+    macro_rules! end_part {
+        () => {
+            if !part.is_empty() {
+                let end = Span::new(line, column + len);
+                tokens.push(Ok((end.clone(), Token::Str(part.clone()), end)));
+                part.clear();
+            }
+        };
+    }
+
+    loop {
+        let c = chars.next().ok_or(LexicalError::UnterminatedString)?;
+        len += 1;
+
+        match c {
+            '\\' => {
+                let (escaped, escape_len) = take_escape(chars)?;
+                part.push(escaped);
+                len += escape_len;
+            }
+            '{' | '}' if chars.clone().next() == Some(c) => {
+                chars.next();
+                part.push(c);
+                len += 1;
+            }
+            '{' => {
+                end_part!();
+                let start = column + len;
+                let (source, source_len) = take_format_expr(chars)?;
+
+                tokens.push(Ok((
+                    Span::new(line, start - 1),
+                    Token::LBracket,
+                    Span::new(line, start),
+                )));
+                tokens.extend(lex_from(&source, line, start));
+                len += source_len;
+                tokens.push(Ok((
+                    Span::new(line, column + len - 1),
+                    Token::RBracket,
+                    Span::new(line, column + len),
+                )));
+            }
+            '}' => return Err(LexicalError::UnknownToken(c)),
+            _ if c == quote => break,
+            _ => part.push(c),
+        }
+    }
+
+    end_part!();
+    tokens.push(Ok((
+        Span::new(line, column + len - 1),
+        Token::FormatEnd,
+        Span::new(line, column + len),
+    )));
+
+    Ok((tokens, len))
+}
+
+/// Reads the source of an expression in a format string up to its closing brace
+///
+/// Returns the source and the number of characters read, including the brace.
+/// A format string nested in the expression has to use the other quote if it
+/// has expressions of its own.
+fn take_format_expr(chars: &mut Chars) -> Result<(String, usize), LexicalError> {
+    let mut source = String::new();
+    let mut depth = 0;
+    // Braces inside strings of the expression are not counted
+    let mut quote = None;
+    let mut escaped = false;
 
     while let Some(c) = chars.next() {
-        if !f(c, l) {
-            return (buf, Some(c));
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some(_) if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '{' => depth += 1,
+                '}' if depth == 0 => {
+                    let len = source.chars().count() + 1;
+                    return Ok((source, len));
+                }
+                '}' => depth -= 1,
+                _ => {}
+            },
         }
 
-        buf.push(c);
-        l = c;
+        source.push(c);
     }
 
-    return (buf, None);
+    Err(LexicalError::UnterminatedString)
 }
 
 #[cfg(test)]
@@ -349,10 +667,12 @@ mod tests {
         expect!("[", vec![LBrace]);
         expect!("]", vec![RBrace]);
         expect!("|", vec![Pipe]);
+        expect!("?", vec![Question]);
         expect!("{", vec![LBracket]);
         expect!("}", vec![RBracket]);
         expect!(";", vec![SemiColon]);
         expect!(":", vec![Colon]);
+        expect!(".", vec![Dot]);
         expect!("=", vec![Eq]);
         expect!("==", vec![EqEq]);
         expect!("!=", vec![NotEq]);
@@ -363,22 +683,135 @@ mod tests {
         expect!("+", vec![Plus]);
         expect!("-", vec![Minus]);
         expect!("->", vec![Arrow]);
+        expect!("=>", vec![FatArrow]);
         expect!("*", vec![Star]);
         expect!("/", vec![Slash]);
+        expect!("**", vec![StarStar]);
+        expect!("//", vec![SlashSlash]);
+        expect!("%", vec![Percent]);
+        expect!("* *", vec![Star, Star]);
+        expect!("a.b", vec![Name("a".to_owned()), Dot, Name("b".to_owned())]);
         expect!("\"test\"", vec![Str("test".to_owned())]);
         expect!("'test'", vec![Str("test".to_owned())]);
     }
 
+    #[test]
+    fn test_numbers() {
+        use super::Token::*;
+
+        expect!("42", vec![Integer(42)]);
+        expect!("4.25", vec![Float(4.25)]);
+        expect!("0.5", vec![Float(0.5)]);
+        expect!("1e3", vec![Float(1000.0)]);
+        expect!("2.5E-2", vec![Float(0.025)]);
+        expect!("6e+1", vec![Float(60.0)]);
+        expect!("3 e", vec![Integer(3), Name("e".to_owned())]);
+        expect!("1.5*2", vec![Float(1.5), Star, Integer(2)]);
+        expect!("9223372036854775808", vec![Integer(9223372036854775808)]);
+        expect!("-3", vec![Minus, Integer(3)]);
+    }
+
+    #[test]
+    fn test_integer_overflow() {
+        assert_eq!(
+            lex("18446744073709551616"),
+            vec![Err(LexicalError::IntegerOverflow(
+                "18446744073709551616".to_owned(),
+                Span::new(1, 1)
+            ))]
+        );
+    }
+
+    #[test]
+    fn test_float_overflow() {
+        assert_eq!(
+            lex("1 1e400"),
+            vec![
+                Ok((Span::new(1, 1), Token::Integer(1), Span::new(1, 2))),
+                Err(LexicalError::FloatOverflow("1e400".to_owned(), Span::new(1, 3)))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_strings() {
+        use super::Token::*;
+
+        expect!("\"\"", vec![Str("".to_owned())]);
+        expect!("'' 1", vec![Str("".to_owned()), Integer(1)]);
+        expect!(r#""a\nb\tc""#, vec![Str("a\nb\tc".to_owned())]);
+        expect!(r#""\"" + '\'' "#, vec![Str("\"".to_owned()), Plus, Str("'".to_owned())]);
+        expect!(r#""a\\" b"#, vec![Str("a\\".to_owned()), Name("b".to_owned())]);
+        expect!(r#""\u{48}\u{1F600}""#, vec![Str("H\u{1F600}".to_owned())]);
+
+        for bad in vec![r#""\q""#, r#""\u{110000}""#, r#""\u48""#, r#""\u{}""#] {
+            match lex(bad)[0] {
+                Err(LexicalError::InvalidEscape(_)) => {}
+                ref other => panic!("{} lexed as {:?}", bad, other),
+            }
+        }
+
+        assert_eq!(lex("\"abc"), vec![Err(LexicalError::UnterminatedString)]);
+    }
+
+    #[test]
+    fn test_format_strings() {
+        use super::Token::*;
+
+        expect!("f\"\"", vec![FormatStart, FormatEnd]);
+        expect!(
+            "f\"total: {a + b}!\"",
+            vec![
+                FormatStart,
+                Str("total: ".to_owned()),
+                LBracket,
+                Name("a".to_owned()),
+                Plus,
+                Name("b".to_owned()),
+                RBracket,
+                Str("!".to_owned()),
+                FormatEnd,
+            ]
+        );
+        expect!(
+            r#"f"{{{"}"}}}\n""#,
+            vec![
+                FormatStart,
+                Str("{".to_owned()),
+                LBracket,
+                Str("}".to_owned()),
+                RBracket,
+                Str("}\n".to_owned()),
+                FormatEnd,
+            ]
+        );
+        expect!("f (1)", vec![Name("f".to_owned()), LParen, Integer(1), RParen]);
+    }
+
     #[test]
     fn test_keywords() {
         use super::Token::*;
 
         expect!("def", vec![Def]);
         expect!("let", vec![Let]);
+        expect!("const", vec![Const]);
+        expect!("import", vec![Import]);
+        expect!("from", vec![From]);
         expect!("if", vec![If]);
         expect!("else", vec![Else]);
         expect!("true", vec![True]);
         expect!("false", vec![False]);
+        expect!("none", vec![None]);
+        expect!("and", vec![And]);
+        expect!("or", vec![Or]);
+        expect!("not", vec![Not]);
+        expect!("step", vec![Step]);
+        expect!("break", vec![Break]);
+        expect!("continue", vec![Continue]);
+        expect!("return", vec![Return]);
+        expect!("struct", vec![Struct]);
+        expect!("enum", vec![Enum]);
+        expect!("match", vec![Match]);
     }
 
     #[test]
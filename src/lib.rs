@@ -13,6 +13,8 @@ pub mod errors;
 pub mod module;
 pub mod object;
 mod parser;
+pub mod resolver;
+pub mod types;
 pub mod vm;
 
 pub fn parse_str(
@@ -1,8 +1,9 @@
-use errors::GearsError;
+use errors::{GearsError, InterOpErrorType};
 use object::{ArcGearsObject, GearsObject};
 use opcodes::*;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
+use types::{types_accept, Declarations, Types};
 
 /// Contains a compiled module
 #[derive(Debug, Clone)]
@@ -10,7 +11,12 @@ pub struct Module {
     name: String,
     function_lookup: HashMap<String, usize>,
     functions: Vec<Function>,
+    structs: Vec<StructDef>,
+    enums: Vec<EnumDef>,
     consts: Vec<ArcGearsObject>,
+    globals: Vec<Global>,
+    // Sets the globals, it is run once when the module is compiled
+    initializer: Option<usize>,
 }
 
 impl Module {
@@ -19,7 +25,11 @@ impl Module {
             name: name,
             function_lookup: HashMap::new(),
             functions: Vec::new(),
+            structs: Vec::new(),
+            enums: Vec::new(),
             consts: Vec::new(),
+            globals: Vec::new(),
+            initializer: None,
         }
     }
 
@@ -42,6 +52,119 @@ impl Module {
         }
     }
 
+    pub fn get_struct(&self, name: &str) -> Option<&StructDef> {
+        self.structs.iter().find(|s| s.name == name)
+    }
+
+    pub fn get_struct_by_index(&self, index: usize) -> Result<&StructDef, GearsError> {
+        match self.structs.get(index) {
+            Some(v) => Ok(v),
+            None => Err(GearsError::InternalCompilerError(format!(
+                "Struct {} does not exist",
+                index
+            ))),
+        }
+    }
+
+    pub fn get_enum(&self, name: &str) -> Option<&EnumDef> {
+        self.enums.iter().find(|e| e.name == name)
+    }
+
+    pub fn get_enum_by_index(&self, index: usize) -> Result<&EnumDef, GearsError> {
+        match self.enums.get(index) {
+            Some(v) => Ok(v),
+            None => Err(GearsError::InternalCompilerError(format!(
+                "Enum {} does not exist",
+                index
+            ))),
+        }
+    }
+
+    /// Returns the function that sets the globals, if the module has any
+    pub fn get_initializer(&self) -> Option<&Function> {
+        self.initializer.map(|index| &self.functions[index])
+    }
+
+    pub fn get_globals(&self) -> &Vec<Global> {
+        &self.globals
+    }
+
+    /// Returns the current value of a global
+    pub fn get_global(&self, name: &str) -> Result<ArcGearsObject, GearsError> {
+        match self.globals.iter().position(|g| g.name == name) {
+            Some(index) => self.load_global(index),
+            None => Err(GearsError::SymbolNotFound(name.to_string())),
+        }
+    }
+
+    /// Sets a global from the host, the value must fit the declared type
+    pub fn set_global(&self, name: &str, value: ArcGearsObject) -> Result<(), GearsError> {
+        let index = match self.globals.iter().position(|g| g.name == name) {
+            Some(index) => index,
+            None => return Err(GearsError::SymbolNotFound(name.to_string())),
+        };
+        let global = &self.globals[index];
+
+        if !global.mutable {
+            return Err(GearsError::InterOpError {
+                error: InterOpErrorType::ConstGlobal,
+                message: format!("{} is a constant and can not be assigned to", name),
+            });
+        }
+
+        if !types_accept(&global.types, &value, self) {
+            return Err(GearsError::InterOpError {
+                error: InterOpErrorType::WrongGlobalType,
+                message: format!(
+                    "{} expects {:?}, received: {}",
+                    name,
+                    global.types,
+                    value.get_type_str()
+                ),
+            });
+        }
+
+        self.store_global(index, value)
+    }
+
+    pub fn load_global(&self, index: usize) -> Result<ArcGearsObject, GearsError> {
+        match self.globals.get(index) {
+            Some(global) => match global.value.read() {
+                Ok(value) => match *value {
+                    Some(ref value) => Ok(value.clone()),
+                    None => Err(GearsError::UninitializedGlobal(global.name.clone())),
+                },
+                Err(_) => Err(GearsError::InternalCompilerError(format!(
+                    "Global {} is poisoned",
+                    global.name
+                ))),
+            },
+            None => Err(GearsError::InternalCompilerError(format!(
+                "Global {} does not exist",
+                index
+            ))),
+        }
+    }
+
+    pub fn store_global(&self, index: usize, value: ArcGearsObject) -> Result<(), GearsError> {
+        match self.globals.get(index) {
+            Some(global) => match global.value.write() {
+                Ok(mut current) => {
+                    *current = Some(value);
+                    Ok(())
+                }
+                Err(_) => Err(GearsError::InternalCompilerError(format!(
+                    "Global {} is poisoned",
+                    global.name
+                ))),
+            },
+            None => Err(GearsError::InternalCompilerError(format!(
+                "Global {} does not exist",
+                index
+            ))),
+        }
+    }
+
     pub fn get_const(&self, index: usize) -> ArcGearsObject {
         self.consts[index].clone()
     }
@@ -64,6 +187,21 @@ impl Module {
         self.insert_const(new_value)
     }
 
+    fn insert_float(&mut self, number: f64) -> usize {
+        // Compare on the bits so 0.0 and -0.0 are kept as separate consts
+        for (index, constant) in self.consts.iter().enumerate() {
+            if let GearsObject::Float(f) = **constant {
+                if f.to_bits() == number.to_bits() {
+                    return index;
+                }
+            }
+        }
+
+        let result = self.consts.len();
+        self.consts.push(Arc::new(GearsObject::Float(number)));
+        result
+    }
+
     fn insert_string(&mut self, string: String) -> usize {
         let new_value = Arc::new(GearsObject::Str(string));
 
@@ -71,9 +209,38 @@ impl Module {
     }
 }
 
+impl Declarations for Module {
+    fn struct_fields(&self, name: &str) -> Option<Vec<(&String, &Types)>> {
+        self.get_struct(name)
+            .map(|s| s.fields.iter().zip(&s.field_types).collect())
+    }
+
+    fn variant_payload(&self, enum_name: &str, variant: &str) -> Option<&Vec<Types>> {
+        let enum_def = self.get_enum(enum_name)?;
+        let index = enum_def.variants.iter().position(|(name, _)| name == variant)?;
+        enum_def.payload_types.get(index)
+    }
+
+    fn function_args(&self, index: usize) -> Option<usize> {
+        self.functions.get(index).map(|f| f.num_args)
+    }
+}
+
 pub struct ModuleBuilder {
     module: Module,
     current_fn: Option<Function>,
+    loops: Vec<LoopContext>,
+    // Functions that are paused while a function literal inside them is built
+    outer_fns: Vec<(Function, Vec<LoopContext>)>,
+    function_literals: usize,
+    // Built in parts, once for every module that is compiled into this one
+    initializer: Option<Function>,
+}
+
+/// Jumps out of a loop that are waiting for their target to be known
+struct LoopContext {
+    breaks: Vec<usize>,
+    continues: Vec<usize>,
 }
 
 impl ModuleBuilder {
@@ -81,30 +248,170 @@ impl ModuleBuilder {
         ModuleBuilder {
             module: Module::new(name),
             current_fn: None,
+            loops: Vec::new(),
+            outer_fns: Vec::new(),
+            function_literals: 0,
+            initializer: None,
         }
     }
 
+    /// Reserves the index of a module level function before it is built
+    ///
+    /// Function literals are built while the module is compiled, so the
+    /// indexes of named functions have to be fixed before any body is built.
+    /// The params and return types are kept when the function is built.
+    pub fn declare_function(
+        &mut self,
+        name: String,
+        params: Vec<Param>,
+        return_types: Types,
+    ) -> usize {
+        let index = self.module.functions.len();
+        let mut function = Function::new(name.clone(), params.len());
+        function.params = params;
+        function.return_types = return_types;
+
+        self.module.function_lookup.insert(name, index);
+        self.module.functions.push(function);
+        index
+    }
+
+    /// Starts building a function, pausing the current one if there is one
     pub fn start_function(&mut self, name: String, num_args: usize) {
-        self.current_fn = Some(Function::new(name, num_args));
+        self.resume_function(Function::new(name, num_args));
     }
 
-    pub fn finish_function(&mut self) {
-        self.opcode(RETURN);
-        let index = self.module.functions.len();
+    fn resume_function(&mut self, function: Function) {
+        if let Some(outer_fn) = self.current_fn.take() {
+            let loops = std::mem::take(&mut self.loops);
+            self.outer_fns.push((outer_fn, loops));
+        }
 
-        match self.current_fn.as_mut() {
-            Some(cur_fn) => {
-                self.module.functions.push(cur_fn.clone());
+        self.current_fn = Some(function);
+    }
 
-                self.module.function_lookup.insert(cur_fn.get_name(), index);
-            }
+    fn restore_outer_function(&mut self) {
+        if let Some((outer_fn, loops)) = self.outer_fns.pop() {
+            self.current_fn = Some(outer_fn);
+            self.loops = loops;
+        }
+    }
+
+    /// Starts building an anonymous function, it is named `@function` and a number
+    pub fn start_function_literal(&mut self, num_args: usize) {
+        let name = format!("@function{}", self.function_literals);
+        self.function_literals += 1;
+        self.start_function(name, num_args);
+    }
+
+    /// Finishes the current function, returning its index in the module
+    pub fn finish_function(&mut self) -> usize {
+        self.opcode(RETURN);
+        let mut index = self.module.functions.len();
+
+        match self.current_fn.take() {
+            Some(mut cur_fn) => match self.module.function_lookup.get(&cur_fn.get_name()) {
+                Some(declared) => {
+                    index = *declared;
+                    let declared = &mut self.module.functions[index];
+                    cur_fn.params = std::mem::take(&mut declared.params);
+                    cur_fn.return_types = std::mem::take(&mut declared.return_types);
+                    self.module.functions[index] = cur_fn;
+                }
+                None => {
+                    self.module.function_lookup.insert(cur_fn.get_name(), index);
+                    self.module.functions.push(cur_fn);
+                }
+            },
             None => {}
         }
 
-        self.current_fn = None;
+        self.restore_outer_function();
+        index
+    }
+
+    /// Adds a struct definition, returning the index used to construct it
+    pub fn add_struct(&mut self, name: String, fields: Vec<String>) -> usize {
+        self.module.structs.push(StructDef {
+            name,
+            fields,
+            field_types: Vec::new(),
+        });
+        self.module.structs.len() - 1
     }
 
-    pub fn build(self) -> Module {
+    /// Sets the types of the fields of a struct, once every type is declared
+    pub fn set_field_types(&mut self, index: usize, field_types: Vec<Types>) {
+        self.module.structs[index].field_types = field_types;
+    }
+
+    /// Adds an enum definition, returning the index used to construct its variants
+    ///
+    /// The variant is stored in one byte of the opcodes, so an enum has at most 256.
+    pub fn add_enum(
+        &mut self,
+        name: String,
+        variants: Vec<(String, usize)>,
+    ) -> Result<usize, GearsError> {
+        if variants.len() > u8::max_value() as usize + 1 {
+            return Err(GearsError::LimitError(format!(
+                "{} has {} variants, the limit is {}",
+                name,
+                variants.len(),
+                u8::max_value() as usize + 1
+            )));
+        }
+
+        self.module.enums.push(EnumDef {
+            name,
+            variants,
+            payload_types: Vec::new(),
+        });
+        Ok(self.module.enums.len() - 1)
+    }
+
+    /// Sets the payload types of each variant of an enum, once every type is declared
+    pub fn set_payload_types(&mut self, index: usize, payload_types: Vec<Vec<Types>>) {
+        self.module.enums[index].payload_types = payload_types;
+    }
+
+    /// Adds a global, reading it fails until the initializer sets it
+    pub fn add_global(&mut self, name: String, types: Types, mutable: bool) -> usize {
+        self.module.globals.push(Global {
+            name,
+            types,
+            mutable,
+            value: RwLock::new(None),
+        });
+        self.module.globals.len() - 1
+    }
+
+    /// Continues building the function that sets the globals, it is named `@init`
+    ///
+    /// Imported modules add to the same initializer, so their globals are set first.
+    pub fn start_initializer(&mut self) {
+        let initializer = match self.initializer.take() {
+            Some(initializer) => initializer,
+            None => Function::new("@init".to_string(), 0),
+        };
+
+        self.resume_function(initializer);
+    }
+
+    /// Pauses the initializer, it is finished when the module is built
+    pub fn finish_initializer(&mut self) {
+        self.initializer = self.current_fn.take();
+        self.restore_outer_function();
+    }
+
+    pub fn build(mut self) -> Module {
+        if let Some(initializer) = self.initializer.take() {
+            self.current_fn = Some(initializer);
+            self.load_none();
+            let index = self.finish_function();
+            self.module.initializer = Some(index);
+        }
+
         self.module
     }
 
@@ -124,30 +431,52 @@ impl ModuleBuilder {
         }
     }
 
-    /// Get the index of the last opcode in the fn
+    /// Writes the index of a function, const, global or type of the module
+    ///
+    /// The index is stored in two bytes, the high byte first.
+    fn index_operand(&mut self, index: usize, kind: &str) -> Result<(), GearsError> {
+        if index > u16::max_value() as usize {
+            return Err(GearsError::LimitError(format!(
+                "Can not use {} {}, the limit is {}",
+                kind,
+                index,
+                u16::max_value()
+            )));
+        }
+
+        self.opcode((index >> 8) as u8);
+        self.opcode(index as u8);
+        Ok(())
+    }
+
+    /// Get the index the next opcode will be written to
     #[inline]
-    fn last_index(&self) -> usize {
-        let index = match self.current_fn.as_ref() {
+    fn next_index(&self) -> usize {
+        match self.current_fn.as_ref() {
             Some(cur_fn) => cur_fn.opcodes.len(),
-            None => 1,
-        };
-        index - 1
+            None => 0,
+        }
     }
 
-    pub fn load_int(&mut self, number: i64) {
+    pub fn load_int(&mut self, number: i64) -> Result<(), GearsError> {
         let index = self.module.insert_int(number);
 
-        // TODO: Handle int overflow with new opcode
+        self.opcode(LOAD_CONST);
+        self.index_operand(index, "const")
+    }
+
+    pub fn load_float(&mut self, number: f64) -> Result<(), GearsError> {
+        let index = self.module.insert_float(number);
 
         self.opcode(LOAD_CONST);
-        self.opcode(index as u8);
+        self.index_operand(index, "const")
     }
 
-    pub fn load_str(&mut self, string: String) {
+    pub fn load_str(&mut self, string: String) -> Result<(), GearsError> {
         let index = self.module.insert_string(string);
 
         self.opcode(LOAD_CONST);
-        self.opcode(index as u8);
+        self.index_operand(index, "const")
     }
 
     pub fn op_add(&mut self) {
@@ -166,6 +495,22 @@ impl ModuleBuilder {
         self.opcode(BIN_DIV);
     }
 
+    pub fn op_floor_div(&mut self) {
+        self.opcode(BIN_FLOOR_DIV);
+    }
+
+    pub fn op_mod(&mut self) {
+        self.opcode(BIN_MOD);
+    }
+
+    pub fn op_pow(&mut self) {
+        self.opcode(BIN_POW);
+    }
+
+    pub fn op_in(&mut self) {
+        self.opcode(BIN_IN);
+    }
+
     pub fn op_eqeq(&mut self) {
         self.opcode(BIN_EQUAL);
     }
@@ -190,6 +535,122 @@ impl ModuleBuilder {
         self.opcode(BIN_GREATER_THAN_EQUAL);
     }
 
+    pub fn build_list(&mut self, count: u8) {
+        self.opcode(BUILD_LIST);
+        self.opcode(count);
+    }
+
+    pub fn build_map(&mut self, count: u8) {
+        self.opcode(BUILD_MAP);
+        self.opcode(count);
+    }
+
+    pub fn build_tuple(&mut self, count: u8) {
+        self.opcode(BUILD_TUPLE);
+        self.opcode(count);
+    }
+
+    /// Pushes the items of a tuple in reverse so they can be stored in order
+    pub fn unpack_tuple(&mut self, count: u8) {
+        self.opcode(UNPACK_TUPLE);
+        self.opcode(count);
+    }
+
+    pub fn build_struct(&mut self, index: usize) -> Result<(), GearsError> {
+        self.opcode(BUILD_STRUCT);
+        self.index_operand(index, "struct")
+    }
+
+    pub fn get_field(&mut self, field: String) -> Result<(), GearsError> {
+        let index = self.module.insert_string(field);
+
+        self.opcode(GET_FIELD);
+        self.index_operand(index, "const")
+    }
+
+    pub fn set_field(&mut self, field: String) -> Result<(), GearsError> {
+        let index = self.module.insert_string(field);
+
+        self.opcode(SET_FIELD);
+        self.index_operand(index, "const")
+    }
+
+    pub fn build_variant(&mut self, enum_index: usize, variant_index: u8) -> Result<(), GearsError> {
+        self.opcode(BUILD_VARIANT);
+        self.index_operand(enum_index, "enum")?;
+        self.opcode(variant_index);
+        Ok(())
+    }
+
+    /// Checks if the value on the stack is the variant of the enum
+    pub fn is_variant(&mut self, enum_index: usize, variant_index: u8) -> Result<(), GearsError> {
+        self.opcode(IS_VARIANT);
+        self.index_operand(enum_index, "enum")?;
+        self.opcode(variant_index);
+        Ok(())
+    }
+
+    pub fn get_payload(&mut self, index: u8) {
+        self.opcode(GET_PAYLOAD);
+        self.opcode(index);
+    }
+
+    /// Creates a function value from the function and the captured values on the stack
+    pub fn load_function(&mut self, index: usize, capture_count: u8) -> Result<(), GearsError> {
+        self.opcode(LOAD_FUNCTION);
+        self.index_operand(index, "function")?;
+        self.opcode(capture_count);
+        Ok(())
+    }
+
+    /// Calls the function value below the args on the stack
+    pub fn call_indirect(&mut self, arg_count: u8) {
+        self.opcode(CALL_INDIRECT);
+        self.opcode(arg_count);
+    }
+
+    pub fn index(&mut self) {
+        self.opcode(INDEX);
+    }
+
+    pub fn store_index(&mut self) {
+        self.opcode(STORE_INDEX);
+    }
+
+    pub fn slice(&mut self) {
+        self.opcode(SLICE);
+    }
+
+    pub fn range_step(&mut self) {
+        self.opcode(RANGE_STEP);
+    }
+
+    pub fn range_next(&mut self) {
+        self.opcode(RANGE_NEXT);
+    }
+
+    pub fn range_check(&mut self) {
+        self.opcode(RANGE_CHECK);
+    }
+
+    pub fn get_iter(&mut self) {
+        self.opcode(GET_ITER);
+    }
+
+    pub fn len(&mut self) {
+        self.opcode(LEN);
+    }
+
+    pub fn pop_top(&mut self) {
+        self.opcode(POP_TOP);
+    }
+
+    /// Copies the top `count` values of the stack, keeping their order
+    pub fn dup_top(&mut self, count: u8) {
+        self.opcode(DUP_TOP);
+        self.opcode(count);
+    }
+
     pub fn store_fast(&mut self, index: u8) {
         self.opcode(STORE_FAST);
         self.opcode(index);
@@ -200,10 +661,25 @@ impl ModuleBuilder {
         self.opcode(index);
     }
 
-    pub fn call_fn(&mut self, index: u8, arg_count: u8) {
+    pub fn load_global(&mut self, index: usize) -> Result<(), GearsError> {
+        self.opcode(LOAD_GLOBAL);
+        self.index_operand(index, "global")
+    }
+
+    pub fn store_global(&mut self, index: usize) -> Result<(), GearsError> {
+        self.opcode(STORE_GLOBAL);
+        self.index_operand(index, "global")
+    }
+
+    pub fn call_fn(&mut self, index: usize, arg_count: u8) -> Result<(), GearsError> {
         self.opcode(CALL_FUNCTION);
-        self.opcode(index);
+        self.index_operand(index, "function")?;
         self.opcode(arg_count);
+        Ok(())
+    }
+
+    pub fn return_value(&mut self) {
+        self.opcode(RETURN);
     }
 
     pub fn load_none(&mut self) {
@@ -218,39 +694,253 @@ impl ModuleBuilder {
         }
     }
 
+    /// Returns the index of the next opcode, which is where the loop restarts
     pub fn start_loop_check(&mut self) -> usize {
-        self.last_index()
+        self.loops.push(LoopContext {
+            breaks: Vec::new(),
+            continues: Vec::new(),
+        });
+        self.next_index()
     }
 
-    pub fn end_loop(&mut self, loop_index: usize, jump_index: usize) {
+    /// Marks where a `continue` in the current loop jumps to
+    pub fn start_continue(&mut self) -> Result<(), GearsError> {
+        let continues = match self.loops.last_mut() {
+            Some(context) => context.continues.split_off(0),
+            None => Vec::new(),
+        };
+
+        for index in continues {
+            self.end_jump(index)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn end_loop(&mut self, loop_index: usize, jump_index: usize) -> Result<(), GearsError> {
+        if loop_index > u16::max_value() as usize {
+            return Err(GearsError::LimitError(format!(
+                "Can not jump back to a loop at byte {}, the limit is {}",
+                loop_index,
+                u16::max_value()
+            )));
+        }
+
         self.opcode(JUMP_ABSOLUTE);
-        self.opcode(loop_index as u8 + 1);
-        let cur_index = self.last_index();
-        self.set_opcode_at(jump_index, (cur_index - jump_index) as u8);
+        self.opcode((loop_index >> 8) as u8);
+        self.opcode(loop_index as u8);
+        self.end_jump(jump_index)?;
+
+        if let Some(context) = self.loops.pop() {
+            for index in context.breaks {
+                self.end_jump(index)?;
+            }
+        }
+
+        Ok(())
     }
 
-    pub fn start_jump_if_false(&mut self) -> usize {
-        self.opcode(JUMP_IF_FALSE);
-        self.opcode(0); // Placeholder
-        self.last_index()
+    /// Jumps to the end of the current loop. Returns false if not in a loop
+    pub fn break_loop(&mut self) -> bool {
+        if self.loops.is_empty() {
+            return false;
+        }
+
+        let index = self.start_jump();
+        if let Some(context) = self.loops.last_mut() {
+            context.breaks.push(index);
+        }
+        true
     }
 
-    pub fn start_else(&mut self, index: usize) -> usize {
-        self.opcode(JUMP);
+    /// Jumps to the continue point of the current loop. Returns false if not in a loop
+    pub fn continue_loop(&mut self) -> bool {
+        if self.loops.is_empty() {
+            return false;
+        }
+
+        let index = self.start_jump();
+        if let Some(context) = self.loops.last_mut() {
+            context.continues.push(index);
+        }
+        true
+    }
+
+    /// Writes a jump with a placeholder offset, returns the index of the offset
+    fn jump_placeholder(&mut self, opcode: u8) -> usize {
+        self.opcode(opcode);
+        let index = self.next_index();
         self.opcode(0); // Placeholder
-        let cur_index = self.last_index();
-        self.set_opcode_at(index, (cur_index - index) as u8);
-        cur_index
+        self.opcode(0);
+        index
+    }
+
+    pub fn start_jump_if_false(&mut self) -> usize {
+        self.jump_placeholder(JUMP_IF_FALSE)
     }
 
-    pub fn end_jump(&mut self, index: usize) {
-        let cur_index = self.last_index();
-        self.set_opcode_at(index, (cur_index - index) as u8);
+    pub fn start_jump_if_true(&mut self) -> usize {
+        self.jump_placeholder(JUMP_IF_TRUE)
+    }
+
+    pub fn start_jump(&mut self) -> usize {
+        self.jump_placeholder(JUMP)
+    }
+
+    pub fn start_else(&mut self, index: usize) -> Result<usize, GearsError> {
+        let else_index = self.jump_placeholder(JUMP);
+        self.end_jump(index)?;
+        Ok(else_index)
+    }
+
+    /// Makes the jump at `index` land on the next opcode
+    ///
+    /// The offset is counted from the end of the jump and is stored in two bytes.
+    pub fn end_jump(&mut self, index: usize) -> Result<(), GearsError> {
+        let offset = self.next_index() - (index + 2);
+
+        if offset > u16::max_value() as usize {
+            return Err(GearsError::LimitError(format!(
+                "Can not jump over {} bytes of code, the limit is {}",
+                offset,
+                u16::max_value()
+            )));
+        }
+
+        self.set_opcode_at(index, (offset >> 8) as u8);
+        self.set_opcode_at(index + 1, offset as u8);
+        Ok(())
     }
 
     pub fn inc_one(&mut self) {
         self.opcode(INC_ONE);
     }
+
+    pub fn op_not(&mut self) {
+        self.opcode(UNARY_NOT);
+    }
+
+    pub fn op_neg(&mut self) {
+        self.opcode(UNARY_NEG);
+    }
+
+    pub fn to_str(&mut self) {
+        self.opcode(TO_STR);
+    }
+}
+
+/// The layout of a struct, used to build new instances
+#[derive(Debug, Clone)]
+pub struct StructDef {
+    name: String,
+    fields: Vec<String>,
+    field_types: Vec<Types>,
+}
+
+impl StructDef {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_fields(&self) -> &Vec<String> {
+        &self.fields
+    }
+}
+
+/// The variants of an enum and the size of each payload
+#[derive(Debug, Clone)]
+pub struct EnumDef {
+    name: String,
+    variants: Vec<(String, usize)>,
+    payload_types: Vec<Vec<Types>>,
+}
+
+impl EnumDef {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_variants(&self) -> &Vec<(String, usize)> {
+        &self.variants
+    }
+}
+
+/// A module level variable
+///
+/// The value can be changed through a shared module, by running code or by the host.
+#[derive(Debug)]
+pub struct Global {
+    name: String,
+    types: Types,
+    mutable: bool,
+    // None until the initializer sets it
+    value: RwLock<Option<ArcGearsObject>>,
+}
+
+impl Global {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_types(&self) -> &Types {
+        &self.types
+    }
+
+    pub fn is_mutable(&self) -> bool {
+        self.mutable
+    }
+}
+
+impl Clone for Global {
+    fn clone(&self) -> Global {
+        let value = match self.value.read() {
+            Ok(value) => value.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        };
+
+        Global {
+            name: self.name.clone(),
+            types: self.types.clone(),
+            mutable: self.mutable,
+            value: RwLock::new(value),
+        }
+    }
+}
+
+/// A declared arg of a function
+#[derive(Debug, Clone)]
+pub struct Param {
+    name: String,
+    types: Types,
+    // The function that computes the value when the arg is left out
+    default: Option<usize>,
+}
+
+impl Param {
+    pub fn new(name: String, types: Types, default: Option<usize>) -> Param {
+        Param {
+            name,
+            types,
+            default,
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_types(&self) -> &Types {
+        &self.types
+    }
+
+    /// Checks if a value given by the host can be passed as this arg
+    pub fn accepts(&self, value: &GearsObject, module: &Module) -> bool {
+        types_accept(&self.types, value, module)
+    }
+
+    pub fn get_default(&self) -> Option<usize> {
+        self.default
+    }
 }
 
 /// A compiled function
@@ -258,6 +948,9 @@ impl ModuleBuilder {
 pub struct Function {
     name: String,
     num_args: usize,
+    // Only known for functions declared by name
+    params: Vec<Param>,
+    return_types: Types,
     opcodes: Opcodes,
 }
 
@@ -267,6 +960,8 @@ impl Function {
             name: name,
             opcodes: Opcodes::new(),
             num_args: num_args,
+            params: Vec::new(),
+            return_types: Vec::new(),
         }
     }
 
@@ -281,6 +976,15 @@ impl Function {
     pub fn num_args(&self) -> usize {
         self.num_args
     }
+
+    pub fn get_params(&self) -> &Vec<Param> {
+        &self.params
+    }
+
+    /// The declared return types, empty unless the function was declared by name
+    pub fn get_return_types(&self) -> &Types {
+        &self.return_types
+    }
 }
 
 pub fn disassemble(module: &Module, function: &str) {
@@ -315,16 +1019,21 @@ pub fn disassemble(module: &Module, function: &str) {
                 print_code!("RETURN", 0);
                 break;
             }
-            CALL_FUNCTION => print_code!("CALL_FUNCTION", 2),
-            JUMP => print_code!("JUMP", 1),
-            JUMP_ABSOLUTE => print_code!("JUMP_ABSOLUTE", 1),
-            JUMP_IF_FALSE => print_code!("JUMP_IF_FALSE", 1),
+            CALL_FUNCTION => print_code!("CALL_FUNCTION", 3),
+            JUMP => print_code!("JUMP", 2),
+            JUMP_ABSOLUTE => print_code!("JUMP_ABSOLUTE", 2),
+            JUMP_IF_FALSE => print_code!("JUMP_IF_FALSE", 2),
+            JUMP_IF_TRUE => print_code!("JUMP_IF_TRUE", 2),
 
             // Binary Opcodes
             BIN_ADD => print_code!("BIN_ADD", 0),
             BIN_SUB => print_code!("BIN_SUB", 0),
             BIN_MUL => print_code!("BIN_MUL", 0),
             BIN_DIV => print_code!("BIN_DIV", 0),
+            BIN_FLOOR_DIV => print_code!("BIN_FLOOR_DIV", 0),
+            BIN_MOD => print_code!("BIN_MOD", 0),
+            BIN_POW => print_code!("BIN_POW", 0),
+            BIN_IN => print_code!("BIN_IN", 0),
             BIN_EQUAL => print_code!("BIN_EQUAL", 0),
             BIN_NOT_EQUAL => print_code!("BIN_NOT_EQUAL", 0),
             BIN_LESS_THAN => print_code!("BIN_LESS_THAN", 0),
@@ -333,17 +1042,54 @@ pub fn disassemble(module: &Module, function: &str) {
             BIN_GREATER_THAN_EQUAL => print_code!("BIN_GREATER_THAN_EQUAL", 0),
 
             // Misc Opcodes
-            LOAD_CONST => print_code!("LOAD_CONST", 1),
+            LOAD_CONST => print_code!("LOAD_CONST", 2),
+
+            // Iter Operations
+            RANGE_STEP => print_code!("RANGE_STEP", 0),
+            RANGE_CHECK => print_code!("RANGE_CHECK", 0),
+            RANGE_NEXT => print_code!("RANGE_NEXT", 0),
+            BUILD_LIST => print_code!("BUILD_LIST", 1),
+            BUILD_MAP => print_code!("BUILD_MAP", 1),
+            STORE_INDEX => print_code!("STORE_INDEX", 0),
+            INDEX => print_code!("INDEX", 0),
+            SLICE => print_code!("SLICE", 0),
+            GET_ITER => print_code!("GET_ITER", 0),
+            LEN => print_code!("LEN", 0),
 
             // Loading and Storing
             LOAD_FAST => print_code!("LOAD_FAST", 1),
             STORE_FAST => print_code!("STORE_FAST", 1),
-            LOAD_TRUE => print_code!("LOAD_TRUE", 1),
-            LOAD_FALSE => print_code!("LOAD_FALSE", 1),
-            LOAD_NONE => print_code!("LOAD_NONE", 1),
+            LOAD_TRUE => print_code!("LOAD_TRUE", 0),
+            LOAD_FALSE => print_code!("LOAD_FALSE", 0),
+            LOAD_NONE => print_code!("LOAD_NONE", 0),
+            POP_TOP => print_code!("POP_TOP", 0),
+            DUP_TOP => print_code!("DUP_TOP", 1),
 
             // Unary
             INC_ONE => print_code!("INC_ONE", 0),
+            UNARY_NOT => print_code!("UNARY_NOT", 0),
+            UNARY_NEG => print_code!("UNARY_NEG", 0),
+            TO_STR => print_code!("TO_STR", 0),
+
+            // Tuple Operations
+            BUILD_TUPLE => print_code!("BUILD_TUPLE", 1),
+            UNPACK_TUPLE => print_code!("UNPACK_TUPLE", 1),
+
+            // Struct Operations
+            BUILD_STRUCT => print_code!("BUILD_STRUCT", 2),
+            GET_FIELD => print_code!("GET_FIELD", 2),
+            SET_FIELD => print_code!("SET_FIELD", 2),
+
+            // Enum Operations
+            BUILD_VARIANT => print_code!("BUILD_VARIANT", 3),
+            IS_VARIANT => print_code!("IS_VARIANT", 3),
+            GET_PAYLOAD => print_code!("GET_PAYLOAD", 1),
+            LOAD_FUNCTION => print_code!("LOAD_FUNCTION", 3),
+            CALL_INDIRECT => print_code!("CALL_INDIRECT", 1),
+
+            // Global Operations
+            LOAD_GLOBAL => print_code!("LOAD_GLOBAL", 2),
+            STORE_GLOBAL => print_code!("STORE_GLOBAL", 2),
             _ => println!("Unexpected opcode!"),
         }
     }
@@ -1,9 +1,11 @@
 use errors::GearsError;
+use std::fmt;
 use std::sync::Arc;
 
 pub type GearsResult = Result<GearsObject, GearsError>;
 pub type ArcGearsResult = Result<ArcGearsObject, GearsError>;
 pub type ArcGearsObject = Arc<GearsObject>;
+pub type MapEntries = Vec<(ArcGearsObject, ArcGearsObject)>;
 
 lazy_static! {
     pub static ref TRUE_OBJ: ArcGearsObject = Arc::new(GearsObject::Bool(true));
@@ -19,7 +21,51 @@ macro_rules! gears_obj {
             $(
                 v.push(gears_obj!($x));
             )*
-            Arc::new(GearsObject::from(v))
+            Arc::new(GearsObject::List(v))
+        }
+    };
+
+    ( tuple $( $x:expr ),* ) => {
+        {
+            let mut v = Vec::new();
+            $(
+                v.push(gears_obj!($x));
+            )*
+            Arc::new(GearsObject::Tuple(v))
+        }
+    };
+
+    ( struct $name:expr, $( $k:expr => $v:expr ),* ) => {
+        {
+            let mut v = Vec::new();
+            $(
+                v.push(($k.to_string(), gears_obj!($v)));
+            )*
+            Arc::new(GearsObject::Struct { name: $name.to_string(), fields: v })
+        }
+    };
+
+    ( enum $name:expr, $variant:expr $(, $x:expr )* ) => {
+        {
+            let mut v = Vec::new();
+            $(
+                v.push(gears_obj!($x));
+            )*
+            Arc::new(GearsObject::Enum {
+                name: $name.to_string(),
+                variant: $variant.to_string(),
+                values: v,
+            })
+        }
+    };
+
+    ( map $( $k:expr => $v:expr ),* ) => {
+        {
+            let mut v = Vec::new();
+            $(
+                v.push((gears_obj!($k), gears_obj!($v)));
+            )*
+            Arc::new(GearsObject::Map(v))
         }
     };
 
@@ -37,6 +83,119 @@ fn create_type_error(op: &str, left: &GearsObject, right: &GearsObject) -> Gears
     ))
 }
 
+/// Returns the result of checked integer math, or an error if it overflowed
+fn checked_int(result: Option<i64>, left: i64, op: &str, right: i64) -> GearsResult {
+    match result {
+        Some(result) => Ok(GearsObject::Int(result)),
+        None => Err(GearsError::ArithmeticError(format!(
+            "ArithmeticError: Integer overflow in {} {} {}",
+            left, op, right
+        ))),
+    }
+}
+
+fn create_zero_division_error(op: &str) -> GearsError {
+    GearsError::ArithmeticError(format!("ArithmeticError: Unable to {} by zero", op))
+}
+
+/// Checks that both sides of a division are numbers before checking for zero,
+/// so dividing the wrong types is reported as a type error
+fn check_divisor(op: &str, left: &GearsObject, right: &GearsObject) -> Result<(), GearsError> {
+    match (left.as_float(), right.as_float()) {
+        (Some(_), Some(0.0)) => Err(create_zero_division_error(op)),
+        (Some(_), Some(_)) => Ok(()),
+        _ => Err(create_type_error(op, left, right)),
+    }
+}
+
+fn float_mod(left: f64, right: f64) -> f64 {
+    left - right * (left / right).floor()
+}
+
+fn create_index_error(index: i64, len: usize) -> GearsError {
+    GearsError::IndexError(format!(
+        "IndexError: Index {} is out of range for length {}",
+        index, len
+    ))
+}
+
+/// Resolves a possibly negative index, counting from the end when negative
+fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    let resolved = if index < 0 { index + len as i64 } else { index };
+
+    if resolved < 0 || resolved >= len as i64 {
+        None
+    } else {
+        Some(resolved as usize)
+    }
+}
+
+/// Resolves a slice bound, clamping it to the length. None uses the default
+fn resolve_bound(bound: &GearsObject, default: usize, len: usize) -> Result<usize, GearsError> {
+    match bound {
+        GearsObject::None => Ok(default),
+        GearsObject::Int(i) => {
+            let resolved = if *i < 0 { *i + len as i64 } else { *i };
+
+            if resolved < 0 {
+                Ok(0)
+            } else if resolved > len as i64 {
+                Ok(len)
+            } else {
+                Ok(resolved as usize)
+            }
+        }
+        _ => Err(create_type_error_unary("slice", bound)),
+    }
+}
+
+fn slice_bounds(
+    start: &GearsObject,
+    end: &GearsObject,
+    len: usize,
+) -> Result<(usize, usize), GearsError> {
+    let start = resolve_bound(start, 0, len)?;
+    let end = resolve_bound(end, len, len)?;
+
+    // A reversed slice is empty rather than an error
+    if end < start {
+        Ok((start, start))
+    } else {
+        Ok((start, end))
+    }
+}
+
+fn create_key_error(key: &GearsObject) -> GearsError {
+    let key_str = match key {
+        GearsObject::Int(i) => i.to_string(),
+        GearsObject::Bool(b) => b.to_string(),
+        GearsObject::Str(s) => format!("{:?}", s),
+        _ => key.get_type_str().to_string(),
+    };
+
+    GearsError::KeyError(format!("KeyError: Key {} is not in the map", key_str))
+}
+
+fn create_field_error(target: &GearsObject, field: &str) -> GearsError {
+    GearsError::TypeError(format!(
+        "TypeError: {} has no field {}",
+        target.get_type_str(),
+        field
+    ))
+}
+
+fn create_unhashable_error(key: &GearsObject) -> GearsError {
+    GearsError::TypeError(format!(
+        "TypeError: {} can not be used as a map key",
+        key.get_type_str()
+    ))
+}
+
+/// Finds the position of a key in the entries of a map
+fn map_position(entries: &MapEntries, key: &GearsObject) -> Option<usize> {
+    entries.iter().position(|(k, _)| **k == *key)
+}
+
 fn create_type_error_unary(op: &str, left: &GearsObject) -> GearsError {
     GearsError::TypeError(format!(
         "TypeError: Unable to perform {} on {}",
@@ -52,31 +211,118 @@ enum CompareDirection {
     GreaterThanEqual,
 }
 
+impl CompareDirection {
+    #[inline]
+    fn apply<T: PartialOrd>(&self, left: &T, right: &T) -> bool {
+        use self::CompareDirection::*;
+
+        match self {
+            LessThan => left < right,
+            GreaterThan => left > right,
+            LessThanEqual => left <= right,
+            GreaterThanEqual => left >= right,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum GearsObject {
     Str(String),
     Int(i64),
+    Float(f64),
     Bool(bool),
     List(Vec<ArcGearsObject>),
+    Tuple(Vec<ArcGearsObject>),
+    /// Entries are kept in insertion order
+    Map(MapEntries),
+    /// Fields are kept in the order they were declared
+    Struct {
+        name: String,
+        fields: Vec<(String, ArcGearsObject)>,
+    },
+    /// A variant of an enum, with the values of its payload
+    Enum {
+        name: String,
+        variant: String,
+        values: Vec<ArcGearsObject>,
+    },
+    /// A function of the module, with the values it captured when it was created
+    Function {
+        index: usize,
+        captures: Vec<ArcGearsObject>,
+    },
     None,
 }
 
 impl GearsObject {
+    /// Builds a map, a repeated key keeps its first position and its last value
+    pub fn new_map(entries: MapEntries) -> GearsResult {
+        let mut map = Vec::new();
+
+        for (key, value) in entries {
+            if !key.is_hashable() {
+                return Err(create_unhashable_error(&key));
+            }
+
+            match map_position(&map, &key) {
+                Some(position) => map[position].1 = value,
+                None => map.push((key, value)),
+            }
+        }
+
+        Ok(GearsObject::Map(map))
+    }
+
+    /// Only immutable primitives can be used as map keys
+    pub fn is_hashable(&self) -> bool {
+        use self::GearsObject::*;
+
+        match self {
+            Int(_) | Str(_) | Bool(_) => true,
+            _ => false,
+        }
+    }
+
     pub fn inc(&self) -> GearsResult {
         use self::GearsObject::*;
 
         match self {
-            Int(l) => Ok(Int(l + 1)),
+            Int(l) => checked_int(l.checked_add(1), *l, "+", 1),
             _ => Err(create_type_error_unary("increment", &self)),
         }
     }
 
+    pub fn not(&self) -> GearsResult {
+        Ok(GearsObject::Bool(!self.as_bool()))
+    }
+
+    pub fn neg(&self) -> GearsResult {
+        use self::GearsObject::*;
+
+        match self {
+            Int(i) => match i.checked_neg() {
+                Some(i) => Ok(Int(i)),
+                _ => Err(GearsError::ArithmeticError(format!(
+                    "ArithmeticError: Integer overflow negating {}",
+                    i
+                ))),
+            },
+            Float(f) => Ok(Float(-f)),
+            _ => Err(create_type_error_unary("negate", &self)),
+        }
+    }
+
     pub fn add(&self, other: &GearsObject) -> GearsResult {
         use self::GearsObject::*;
 
         match self {
             Int(l) => match other {
-                Int(r) => Ok(Int(l + r)),
+                Int(r) => checked_int(l.checked_add(*r), *l, "+", *r),
+                Float(r) => Ok(Float(*l as f64 + r)),
+                _ => Err(create_type_error("add", &self, &other)),
+            },
+            Float(l) => match other.as_float() {
+                Some(r) => Ok(Float(l + r)),
                 _ => Err(create_type_error("add", &self, &other)),
             },
             Str(ref l) => match other {
@@ -92,7 +338,12 @@ impl GearsObject {
 
         match self {
             Int(l) => match other {
-                Int(r) => Ok(Int(l - r)),
+                Int(r) => checked_int(l.checked_sub(*r), *l, "-", *r),
+                Float(r) => Ok(Float(*l as f64 - r)),
+                _ => Err(create_type_error("sub", &self, &other)),
+            },
+            Float(l) => match other.as_float() {
+                Some(r) => Ok(Float(l - r)),
                 _ => Err(create_type_error("sub", &self, &other)),
             },
             _ => Err(create_type_error("sub", &self, &other)),
@@ -104,7 +355,12 @@ impl GearsObject {
 
         match self {
             Int(l) => match other {
-                Int(r) => Ok(Int(l * r)),
+                Int(r) => checked_int(l.checked_mul(*r), *l, "*", *r),
+                Float(r) => Ok(Float(*l as f64 * r)),
+                _ => Err(create_type_error("mul", &self, &other)),
+            },
+            Float(l) => match other.as_float() {
+                Some(r) => Ok(Float(l * r)),
                 _ => Err(create_type_error("mul", &self, &other)),
             },
             _ => Err(create_type_error("mul", &self, &other)),
@@ -114,15 +370,343 @@ impl GearsObject {
     pub fn div(&self, other: &GearsObject) -> GearsResult {
         use self::GearsObject::*;
 
+        check_divisor("div", self, other)?;
+
         match self {
             Int(l) => match other {
-                Int(r) => Ok(Int(l / r)),
+                Int(r) => checked_int(l.checked_div(*r), *l, "/", *r),
+                Float(r) => Ok(Float(*l as f64 / r)),
+                _ => Err(create_type_error("div", &self, &other)),
+            },
+            Float(l) => match other.as_float() {
+                Some(r) => Ok(Float(l / r)),
                 _ => Err(create_type_error("div", &self, &other)),
             },
             _ => Err(create_type_error("div", &self, &other)),
         }
     }
 
+    /// Division that rounds towards negative infinity
+    pub fn floor_div(&self, other: &GearsObject) -> GearsResult {
+        use self::GearsObject::*;
+
+        check_divisor("floor div", self, other)?;
+
+        match self {
+            Int(l) => match other {
+                Int(r) => match (l.checked_div(*r), l.checked_rem(*r)) {
+                    (Some(quotient), Some(remainder)) => {
+                        if remainder != 0 && ((*l < 0) != (*r < 0)) {
+                            Ok(Int(quotient - 1))
+                        } else {
+                            Ok(Int(quotient))
+                        }
+                    }
+                    _ => checked_int(Option::None, *l, "//", *r),
+                },
+                Float(r) => Ok(Float((*l as f64 / r).floor())),
+                _ => Err(create_type_error("floor div", &self, &other)),
+            },
+            Float(l) => match other.as_float() {
+                Some(r) => Ok(Float((l / r).floor())),
+                _ => Err(create_type_error("floor div", &self, &other)),
+            },
+            _ => Err(create_type_error("floor div", &self, &other)),
+        }
+    }
+
+    /// Modulo where the result takes the sign of the divisor
+    pub fn modulo(&self, other: &GearsObject) -> GearsResult {
+        use self::GearsObject::*;
+
+        check_divisor("modulo", self, other)?;
+
+        match self {
+            Int(l) => match other {
+                Int(r) => {
+                    let remainder = l.wrapping_rem(*r);
+
+                    if remainder != 0 && ((remainder < 0) != (*r < 0)) {
+                        Ok(Int(remainder + r))
+                    } else {
+                        Ok(Int(remainder))
+                    }
+                }
+                Float(r) => Ok(Float(float_mod(*l as f64, *r))),
+                _ => Err(create_type_error("modulo", &self, &other)),
+            },
+            Float(l) => match other.as_float() {
+                Some(r) => Ok(Float(float_mod(*l, r))),
+                _ => Err(create_type_error("modulo", &self, &other)),
+            },
+            _ => Err(create_type_error("modulo", &self, &other)),
+        }
+    }
+
+    pub fn pow(&self, other: &GearsObject) -> GearsResult {
+        use self::GearsObject::*;
+
+        match self {
+            Int(l) => match other {
+                Int(r) => {
+                    if *r < 0 {
+                        return Err(GearsError::ArithmeticError(format!(
+                            "ArithmeticError: Negative exponent {} on an Integer",
+                            r
+                        )));
+                    }
+
+                    // Bases 0 and ±1 never overflow, whatever the exponent
+                    let result = match l {
+                        0 | 1 if *r > 0 => Some(*l),
+                        -1 => Some(if r % 2 == 0 { 1 } else { -1 }),
+                        _ if *r <= u32::max_value() as i64 => l.checked_pow(*r as u32),
+                        _ => Option::None,
+                    };
+
+                    match result {
+                        Some(result) => Ok(Int(result)),
+                        _ => Err(GearsError::ArithmeticError(format!(
+                            "ArithmeticError: Integer overflow in {} ** {}",
+                            l, r
+                        ))),
+                    }
+                }
+                Float(r) => Ok(Float((*l as f64).powf(*r))),
+                _ => Err(create_type_error("pow", &self, &other)),
+            },
+            Float(l) => match other.as_float() {
+                Some(r) => Ok(Float(l.powf(r))),
+                _ => Err(create_type_error("pow", &self, &other)),
+            },
+            _ => Err(create_type_error("pow", &self, &other)),
+        }
+    }
+
+    pub fn index(&self, index: &GearsObject) -> ArcGearsResult {
+        use self::GearsObject::*;
+
+        if let Map(m) = self {
+            return match map_position(m, index) {
+                Some(position) => Ok(m[position].1.clone()),
+                _ => Err(create_key_error(index)),
+            };
+        }
+
+        let i = match index {
+            Int(i) => *i,
+            _ => return Err(create_type_error("index", &self, &index)),
+        };
+
+        match self {
+            List(l) | Tuple(l) => match resolve_index(i, l.len()) {
+                Some(resolved) => Ok(l[resolved].clone()),
+                _ => Err(create_index_error(i, l.len())),
+            },
+            Str(s) => {
+                let len = s.chars().count();
+
+                match resolve_index(i, len) {
+                    Some(resolved) => Ok(Arc::new(Str(s
+                        .chars()
+                        .nth(resolved)
+                        .unwrap()
+                        .to_string()))),
+                    _ => Err(create_index_error(i, len)),
+                }
+            }
+            _ => Err(create_type_error("index", &self, &index)),
+        }
+    }
+
+    /// Returns a copy with the value stored at the index or key
+    pub fn store_index(&self, index: ArcGearsObject, value: ArcGearsObject) -> GearsResult {
+        use self::GearsObject::*;
+
+        match (self, &*index) {
+            (List(l), Int(i)) => match resolve_index(*i, l.len()) {
+                Some(resolved) => {
+                    let mut list = l.clone();
+                    list[resolved] = value;
+                    Ok(List(list))
+                }
+                _ => Err(create_index_error(*i, l.len())),
+            },
+            (Map(m), _) => {
+                if !index.is_hashable() {
+                    return Err(create_unhashable_error(&index));
+                }
+
+                let mut map = m.clone();
+
+                match map_position(&map, &index) {
+                    Some(position) => map[position].1 = value,
+                    _ => map.push((index, value)),
+                }
+
+                Ok(Map(map))
+            }
+            _ => Err(create_type_error("index assignment", &self, &index)),
+        }
+    }
+
+    /// Checks if the item is in a list, a substring of a string or a key in a map
+    pub fn contains(&self, item: &GearsObject) -> GearsResult {
+        use self::GearsObject::*;
+
+        match (self, item) {
+            (List(l), _) | (Tuple(l), _) => Ok(Bool(l.iter().any(|e| e._equal(item)))),
+            (Str(s), Str(sub)) => Ok(Bool(s.contains(sub.as_str()))),
+            (Map(m), _) => Ok(Bool(map_position(m, item).is_some())),
+            _ => Err(create_type_error("in", &item, &self)),
+        }
+    }
+
+    pub fn slice(&self, start: &GearsObject, end: &GearsObject) -> GearsResult {
+        use self::GearsObject::*;
+
+        match self {
+            List(l) => {
+                let (start, end) = slice_bounds(start, end, l.len())?;
+                Ok(List(l[start..end].to_vec()))
+            }
+            Str(s) => {
+                let (start, end) = slice_bounds(start, end, s.chars().count())?;
+                Ok(Str(s.chars().skip(start).take(end - start).collect()))
+            }
+            _ => Err(create_type_error_unary("slice", &self)),
+        }
+    }
+
+    /// Returns the default step of a range from self to end
+    pub fn range_step(&self, end: &GearsObject) -> GearsResult {
+        use self::GearsObject::*;
+
+        match self {
+            Int(start) => match end {
+                Int(end) => Ok(Int(if start <= end { 1 } else { -1 })),
+                _ => Err(create_type_error("range", &self, &end)),
+            },
+            _ => Err(create_type_error("range", &self, &end)),
+        }
+    }
+
+    /// Moves a range at self on by step
+    ///
+    /// The end of a range is never included, so a step past the limits of an
+    /// int stops at the limit and the range check ends the loop.
+    pub fn range_next(&self, step: &GearsObject) -> GearsResult {
+        use self::GearsObject::*;
+
+        match (self, step) {
+            (Int(current), Int(step)) => Ok(Int(current.saturating_add(*step))),
+            _ => Err(create_type_error("range", &self, &step)),
+        }
+    }
+
+    /// Checks if a range at self has not passed the end in the direction of step
+    pub fn range_check(&self, end: &GearsObject, step: &GearsObject) -> GearsResult {
+        use self::GearsObject::*;
+
+        match (self, end, step) {
+            (Int(_), Int(_), Int(0)) => Err(GearsError::ArithmeticError(
+                "ArithmeticError: Range step cannot be zero".to_string(),
+            )),
+            (Int(current), Int(end), Int(step)) => Ok(Bool(if *step > 0 {
+                current < end
+            } else {
+                current > end
+            })),
+            _ => Err(create_type_error("range", &self, &end)),
+        }
+    }
+
+    pub fn get_field(&self, field: &str) -> ArcGearsResult {
+        match self {
+            GearsObject::Struct { fields, .. } => match fields.iter().find(|(f, _)| f == field) {
+                Some((_, value)) => Ok(value.clone()),
+                None => Err(create_field_error(self, field)),
+            },
+            _ => Err(create_field_error(self, field)),
+        }
+    }
+
+    /// Returns a copy of the struct with the field set to value
+    pub fn set_field(&self, field: &str, value: ArcGearsObject) -> GearsResult {
+        match self {
+            GearsObject::Struct { name, fields } => {
+                match fields.iter().position(|(f, _)| f == field) {
+                    Some(position) => {
+                        let mut fields = fields.clone();
+                        fields[position].1 = value;
+
+                        Ok(GearsObject::Struct {
+                            name: name.clone(),
+                            fields,
+                        })
+                    }
+                    None => Err(create_field_error(self, field)),
+                }
+            }
+            _ => Err(create_field_error(self, field)),
+        }
+    }
+
+    /// Checks if this is the named variant of the named enum
+    pub fn is_variant(&self, enum_name: &str, variant_name: &str) -> bool {
+        match self {
+            GearsObject::Enum { name, variant, .. } => name == enum_name && variant == variant_name,
+            _ => false,
+        }
+    }
+
+    /// Returns a value from the payload of an enum variant
+    pub fn payload(&self, index: usize) -> ArcGearsResult {
+        match self {
+            GearsObject::Enum { values, .. } if index < values.len() => Ok(values[index].clone()),
+            _ => Err(create_type_error_unary("payload", &self)),
+        }
+    }
+
+    /// Returns the items of a tuple with exactly `count` items
+    pub fn unpack(&self, count: usize) -> Result<Vec<ArcGearsObject>, GearsError> {
+        match self {
+            GearsObject::Tuple(t) if t.len() == count => Ok(t.clone()),
+            _ => Err(GearsError::TypeError(format!(
+                "TypeError: Unable to unpack {} into {} names",
+                self.get_type_str(),
+                count
+            ))),
+        }
+    }
+
+    /// Returns a list of the items to iterate over
+    pub fn iter(&self) -> GearsResult {
+        use self::GearsObject::*;
+
+        match self {
+            List(l) | Tuple(l) => Ok(List(l.clone())),
+            Str(s) => Ok(List(
+                s.chars()
+                    .map(|c| Arc::new(Str(c.to_string())))
+                    .collect(),
+            )),
+            Map(m) => Ok(List(m.iter().map(|(k, _)| k.clone()).collect())),
+            _ => Err(create_type_error_unary("iterate", &self)),
+        }
+    }
+
+    pub fn len(&self) -> GearsResult {
+        use self::GearsObject::*;
+
+        match self {
+            List(l) | Tuple(l) => Ok(Int(l.len() as i64)),
+            Str(s) => Ok(Int(s.chars().count() as i64)),
+            Map(m) => Ok(Int(m.len() as i64)),
+            _ => Err(create_type_error_unary("len", &self)),
+        }
+    }
+
     #[inline]
     fn _equal(&self, other: &GearsObject) -> bool {
         use self::GearsObject::*;
@@ -130,6 +714,11 @@ impl GearsObject {
         match self {
             Int(l) => match other {
                 Int(r) => l == r,
+                Float(r) => *l as f64 == *r,
+                _ => false,
+            },
+            Float(l) => match other.as_float() {
+                Some(r) => *l == r,
                 _ => false,
             },
             Bool(l) => match other {
@@ -144,7 +733,25 @@ impl GearsObject {
                 List(r) => l == r,
                 _ => false,
             },
-            None => false,
+            Tuple(l) => match other {
+                Tuple(r) => l == r,
+                _ => false,
+            },
+            Struct { .. } | Enum { .. } | Function { .. } => self == other,
+            // Maps are equal with the same entries in any order
+            Map(l) => match other {
+                Map(r) => {
+                    l.len() == r.len() && l.iter().all(|(k, v)| match map_position(r, k) {
+                        Some(position) => v._equal(&r[position].1),
+                        _ => false,
+                    })
+                }
+                _ => false,
+            },
+            None => match other {
+                None => true,
+                _ => false,
+            },
         }
     }
 
@@ -162,16 +769,12 @@ impl GearsObject {
 
         match self {
             Int(l) => match other {
-                Int(r) => {
-                    use self::CompareDirection::*;
-
-                    match dir {
-                        LessThan => Ok(Bool(l < r)),
-                        GreaterThan => Ok(Bool(l > r)),
-                        LessThanEqual => Ok(Bool(l <= r)),
-                        GreaterThanEqual => Ok(Bool(l >= r)),
-                    }
-                }
+                Int(r) => Ok(Bool(dir.apply(l, r))),
+                Float(r) => Ok(Bool(dir.apply(&(*l as f64), r))),
+                _ => Err(create_type_error(op, &self, &other)),
+            },
+            Float(l) => match other.as_float() {
+                Some(r) => Ok(Bool(dir.apply(l, &r))),
                 _ => Err(create_type_error(op, &self, &other)),
             },
             _ => Err(create_type_error(op, &self, &other)),
@@ -194,37 +797,150 @@ impl GearsObject {
         self.compare(other, CompareDirection::GreaterThanEqual, ">=")
     }
 
+    /// Converts any value to a string
+    pub fn to_str(&self) -> GearsResult {
+        Ok(GearsObject::Str(self.to_string()))
+    }
+
     pub fn get_type_str(&self) -> &str {
         use self::GearsObject::*;
 
         match self {
             Int(_) => "Integer",
+            Float(_) => "Float",
             Bool(_) => "Bool",
             Str(_) => "String",
             List(_) => "List",
+            Tuple(_) => "Tuple",
+            Map(_) => "Map",
+            Struct { name, .. } | Enum { name, .. } => name,
+            Function { .. } => "Function",
             None => "NoneType",
         }
     }
 
+    /// Returns the value as a float if it is numeric
+    pub fn as_float(&self) -> Option<f64> {
+        use self::GearsObject::*;
+
+        match self {
+            Int(i) => Some(*i as f64),
+            Float(f) => Some(*f),
+            _ => Option::None,
+        }
+    }
+
     pub fn as_bool(&self) -> bool {
         use self::GearsObject::*;
 
         match self {
             Bool(b) => *b,
             Int(i) => *i != 0,
+            Float(f) => *f != 0.0,
             Str(s) => s.len() > 0,
-            List(l) => l.len() > 0,
+            List(l) | Tuple(l) => l.len() > 0,
+            Map(m) => m.len() > 0,
+            Struct { .. } | Enum { .. } | Function { .. } => true,
             None => false,
         }
     }
 }
 
+/// Formats values the way they are written in gears, strings are left unquoted
+impl fmt::Display for GearsObject {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::GearsObject::*;
+
+        match self {
+            Str(s) => write!(f, "{}", s),
+            Int(i) => write!(f, "{}", i),
+            // Debug keeps the fraction of whole floats, 2.0 instead of 2
+            Float(n) => write!(f, "{:?}", n),
+            Bool(b) => write!(f, "{}", b),
+            List(items) => {
+                write!(f, "[")?;
+                fmt_items(f, items)?;
+                write!(f, "]")
+            }
+            Tuple(items) => {
+                write!(f, "(")?;
+                fmt_items(f, items)?;
+                write!(f, ")")
+            }
+            Map(entries) if entries.is_empty() => write!(f, "[:]"),
+            Map(entries) => {
+                write!(f, "[")?;
+
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+
+                    fmt_item(f, key)?;
+                    write!(f, ": ")?;
+                    fmt_item(f, value)?;
+                }
+
+                write!(f, "]")
+            }
+            Struct { name, fields } => {
+                write!(f, "{}(", name)?;
+
+                for (index, (field, value)) in fields.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+
+                    write!(f, "{}: ", field)?;
+                    fmt_item(f, value)?;
+                }
+
+                write!(f, ")")
+            }
+            Enum { variant, values, .. } if values.is_empty() => write!(f, "{}", variant),
+            Enum { variant, values, .. } => {
+                write!(f, "{}(", variant)?;
+                fmt_items(f, values)?;
+                write!(f, ")")
+            }
+            Function { .. } => write!(f, "<function>"),
+            None => write!(f, "none"),
+        }
+    }
+}
+
+/// Formats a value inside a container, where strings are quoted
+fn fmt_item(f: &mut fmt::Formatter, item: &GearsObject) -> fmt::Result {
+    match item {
+        GearsObject::Str(s) => write!(f, "{:?}", s),
+        _ => write!(f, "{}", item),
+    }
+}
+
+fn fmt_items(f: &mut fmt::Formatter, items: &[ArcGearsObject]) -> fmt::Result {
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            write!(f, ", ")?;
+        }
+
+        fmt_item(f, item)?;
+    }
+
+    Ok(())
+}
+
 impl From<i64> for GearsObject {
     fn from(i: i64) -> GearsObject {
         GearsObject::Int(i)
     }
 }
 
+impl From<f64> for GearsObject {
+    fn from(f: f64) -> GearsObject {
+        GearsObject::Float(f)
+    }
+}
+
 impl From<bool> for GearsObject {
     fn from(b: bool) -> GearsObject {
         GearsObject::Bool(b)
@@ -242,3 +958,9 @@ impl From<Vec<ArcGearsObject>> for GearsObject {
         GearsObject::List(v)
     }
 }
+
+impl From<MapEntries> for GearsObject {
+    fn from(entries: MapEntries) -> GearsObject {
+        GearsObject::Map(entries)
+    }
+}
@@ -24,6 +24,7 @@ gen_opcodes!(
     JUMP: 2,
     JUMP_ABSOLUTE: 3,
     JUMP_IF_FALSE: 4,
+    JUMP_IF_TRUE: 5,
 
     // Binary Opcodes
     BIN_ADD: 10,
@@ -41,7 +42,15 @@ gen_opcodes!(
     LOAD_CONST: 20,
 
     // Iter Operations
+    BUILD_MAP: 21,
+    STORE_INDEX: 22,
+    RANGE_STEP: 23,
+    RANGE_CHECK: 24,
     BUILD_LIST: 25,
+    INDEX: 26,
+    SLICE: 27,
+    GET_ITER: 28,
+    LEN: 29,
 
     // Loading and Storing
     LOAD_FAST: 30,
@@ -49,7 +58,41 @@ gen_opcodes!(
     LOAD_TRUE: 32,
     LOAD_FALSE: 33,
     LOAD_NONE: 34,
+    POP_TOP: 35,
+    DUP_TOP: 36,
 
     // Unary Operations
-    INC_ONE: 40
+    INC_ONE: 40,
+    UNARY_NOT: 41,
+    UNARY_NEG: 42,
+    TO_STR: 43,
+
+    // Extended Binary Opcodes
+    BIN_MOD: 50,
+    BIN_POW: 51,
+    BIN_FLOOR_DIV: 52,
+    BIN_IN: 53,
+    RANGE_NEXT: 54,
+
+    // Tuple Operations
+    BUILD_TUPLE: 60,
+    UNPACK_TUPLE: 61,
+
+    // Struct Operations
+    BUILD_STRUCT: 70,
+    GET_FIELD: 71,
+    SET_FIELD: 72,
+
+    // Enum Operations
+    BUILD_VARIANT: 73,
+    IS_VARIANT: 74,
+    GET_PAYLOAD: 75,
+
+    // Function Operations
+    LOAD_FUNCTION: 80,
+    CALL_INDIRECT: 81,
+
+    // Global Operations
+    LOAD_GLOBAL: 90,
+    STORE_GLOBAL: 91
 );
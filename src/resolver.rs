@@ -0,0 +1,65 @@
+use errors::GearsError;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::PathBuf;
+
+/// Finds the source of the modules a gears module imports
+pub trait ModuleResolver {
+    /// Returns the source of the module `name`
+    fn resolve(&self, name: &str) -> Result<String, GearsError>;
+}
+
+/// Resolves `import utils;` to the file `utils.gs` in a directory
+pub struct FileResolver {
+    root: PathBuf,
+}
+
+impl FileResolver {
+    pub fn new<P: Into<PathBuf>>(root: P) -> FileResolver {
+        FileResolver { root: root.into() }
+    }
+}
+
+impl ModuleResolver for FileResolver {
+    fn resolve(&self, name: &str) -> Result<String, GearsError> {
+        let path = self.root.join(format!("{}.gs", name));
+
+        if !path.is_file() {
+            return Err(GearsError::ModuleNotFound(name.to_string()));
+        }
+
+        let mut f = File::open(path)?;
+        let mut contents = String::new();
+        f.read_to_string(&mut contents)?;
+        Ok(contents)
+    }
+}
+
+/// Resolves modules from sources kept in memory
+#[derive(Default)]
+pub struct MemoryResolver {
+    modules: HashMap<String, String>,
+}
+
+impl MemoryResolver {
+    pub fn new() -> MemoryResolver {
+        MemoryResolver {
+            modules: HashMap::new(),
+        }
+    }
+
+    /// Adds the source of a module, replacing any earlier source for the name
+    pub fn add_module(&mut self, name: &str, source: &str) {
+        self.modules.insert(name.to_string(), source.to_string());
+    }
+}
+
+impl ModuleResolver for MemoryResolver {
+    fn resolve(&self, name: &str) -> Result<String, GearsError> {
+        match self.modules.get(name) {
+            Some(source) => Ok(source.clone()),
+            None => Err(GearsError::ModuleNotFound(name.to_string())),
+        }
+    }
+}
@@ -1,83 +1,118 @@
 use std::collections::HashMap;
-
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Type {
-    name: String,
-}
-
-impl Type {
-    pub fn new_none() -> Type {
-        Type {
-            name: "none".to_owned(),
-        }
-    }
-
-    pub fn new_int() -> Type {
-        Type {
-            name: "int".to_owned(),
-        }
-    }
-
-    pub fn new_bool() -> Type {
-        Type {
-            name: "bool".to_owned(),
-        }
-    }
-
-    pub fn new_str() -> Type {
-        Type {
-            name: "str".to_owned(),
-        }
-    }
-}
-
-impl From<String> for Type {
-    fn from(name: String) -> Type {
-        Type { name }
-    }
-}
-
-impl<'a> From<&'a str> for Type {
-    fn from(name: &str) -> Type {
-        Type {
-            name: name.to_string(),
-        }
-    }
-}
-
-pub type Types = Vec<Type>;
+use types::Types;
 
 #[derive(Debug, Clone)]
 pub enum SymbolType {
+    /// The args are kept in declaration order
     Function {
-        arg_types: HashMap<String, Types>,
+        /// The types of a generic function refer to its type params as `Type::Param`
+        type_params: Vec<String>,
+        arg_types: Vec<(String, Types)>,
+        /// The index of the function that computes each arg's default value
+        defaults: Vec<Option<usize>>,
         return_types: Types,
     },
+    /// Constants can not be reassigned
+    ///
+    /// `declared` are the types the variable was defined with, assignments are
+    /// checked against them. `types` can be narrower for part of a scope.
     Variable {
         types: Types,
+        declared: Types,
+        mutable: bool,
+    },
+    /// Fields are kept in declaration order, which is also the constructor order
+    ///
+    /// The type name is what values of the type are called, the types of an
+    /// imported module are named `module.Type`.
+    Struct {
+        type_name: String,
+        fields: Vec<(String, Types)>,
+    },
+    /// The payload types of each variant, in declaration order
+    Enum {
+        type_name: String,
+        variants: Vec<(String, Vec<Types>)>,
     },
+    /// A variant of the enum with the type name `enum_name`, the symbol index
+    /// is the enum's index
+    Variant {
+        enum_name: String,
+        variant_index: u8,
+        payload: Vec<Types>,
+    },
+    /// An imported module and the names it defines
+    Module {
+        symbols: HashMap<String, Symbol>,
+    },
+    /// A type param of the generic function being compiled
+    TypeParam,
 }
 
 #[derive(Debug, Clone)]
 pub struct Symbol {
     sym_type: SymbolType,
-    index: u8,
+    index: usize,
 }
 
 impl Symbol {
-    fn new_fn(index: u8, arg_types: HashMap<String, Types>, return_types: Types) -> Symbol {
+    fn new_fn(
+        index: usize,
+        type_params: Vec<String>,
+        arg_types: Vec<(String, Types)>,
+        defaults: Vec<Option<usize>>,
+        return_types: Types,
+    ) -> Symbol {
         Symbol {
             sym_type: SymbolType::Function {
+                type_params,
                 arg_types,
+                defaults,
                 return_types,
             },
             index: index,
         }
     }
 
-    fn new_var(index: u8, types: Types) -> Symbol {
+    fn new_var(index: usize, types: Types, mutable: bool) -> Symbol {
+        Symbol::new_narrowed(index, types.clone(), types, mutable)
+    }
+
+    fn new_narrowed(index: usize, types: Types, declared: Types, mutable: bool) -> Symbol {
+        Symbol {
+            sym_type: SymbolType::Variable {
+                types,
+                declared,
+                mutable,
+            },
+            index: index,
+        }
+    }
+
+    fn new_struct(index: usize, type_name: String, fields: Vec<(String, Types)>) -> Symbol {
+        Symbol {
+            sym_type: SymbolType::Struct { type_name, fields },
+            index: index,
+        }
+    }
+
+    fn new_enum(index: usize, type_name: String, variants: Vec<(String, Vec<Types>)>) -> Symbol {
+        Symbol {
+            sym_type: SymbolType::Enum {
+                type_name,
+                variants,
+            },
+            index: index,
+        }
+    }
+
+    fn new_variant(index: usize, enum_name: String, variant_index: u8, payload: Vec<Types>) -> Symbol {
         Symbol {
-            sym_type: SymbolType::Variable { types },
+            sym_type: SymbolType::Variant {
+                enum_name,
+                variant_index,
+                payload,
+            },
             index: index,
         }
     }
@@ -86,7 +121,7 @@ impl Symbol {
         &self.sym_type
     }
 
-    pub fn get_index(&self) -> &u8 {
+    pub fn get_index(&self) -> &usize {
         &self.index
     }
 }
@@ -94,6 +129,9 @@ impl Symbol {
 pub struct SymbolTable<'a> {
     parent: Option<&'a SymbolTable<'a>>,
     symbols: HashMap<String, Symbol>,
+    return_types: Option<Types>,
+    // Redefining a name takes a new slot, so this can be more than the symbols
+    slots: u8,
 }
 
 impl<'a> SymbolTable<'a> {
@@ -101,6 +139,8 @@ impl<'a> SymbolTable<'a> {
         SymbolTable {
             parent: None,
             symbols: HashMap::new(),
+            return_types: None,
+            slots: 0,
         }
     }
 
@@ -108,6 +148,37 @@ impl<'a> SymbolTable<'a> {
         SymbolTable {
             symbols: HashMap::new(),
             parent: Some(&self),
+            return_types: None,
+            slots: 0,
+        }
+    }
+
+    /// Pushes the top level scope of a function that returns `return_types`
+    pub fn push_function(&'a self, return_types: Types) -> SymbolTable<'a> {
+        SymbolTable {
+            symbols: HashMap::new(),
+            parent: Some(&self),
+            return_types: Some(return_types),
+            slots: 0,
+        }
+    }
+
+    /// Returns the declared return types of the enclosing function
+    pub fn return_types(&self) -> Option<&Types> {
+        match self.return_types {
+            Some(ref e) => Some(e),
+            None => match self.parent {
+                Some(p) => p.return_types(),
+                None => None,
+            },
+        }
+    }
+
+    /// Returns the module level scope
+    pub fn global(&self) -> &SymbolTable<'a> {
+        match self.parent {
+            Some(p) => p.global(),
+            None => self,
         }
     }
 
@@ -117,30 +188,153 @@ impl<'a> SymbolTable<'a> {
 
     fn get_next_index(&self) -> u8 {
         if self.parent.is_none() || self.parent.unwrap().is_global() {
-            self.symbols.len() as u8
+            self.slots
         } else {
-            self.parent.unwrap().get_next_index() + self.symbols.len() as u8
+            self.parent.unwrap().get_next_index() + self.slots
         }
     }
 
+    /// Defines a function, the index is the function's index in the module
     pub fn def_fn(
         &mut self,
         name: String,
-        arg_types: HashMap<String, Types>,
+        index: usize,
+        type_params: Vec<String>,
+        arg_types: Vec<(String, Types)>,
+        defaults: Vec<Option<usize>>,
         return_types: Types,
-    ) -> u8 {
-        let index = self.get_next_index();
+    ) {
+        self.symbols.insert(
+            name,
+            Symbol::new_fn(index, type_params, arg_types, defaults, return_types),
+        );
+    }
+
+    /// Defines a type param, it does not take a slot
+    pub fn def_type_param(&mut self, name: String) {
+        self.symbols.insert(
+            name,
+            Symbol {
+                sym_type: SymbolType::TypeParam,
+                index: 0,
+            },
+        );
+    }
+
+    /// Defines an imported module, its names are used as `module.name`
+    pub fn def_module(&mut self, name: String, symbols: HashMap<String, Symbol>) {
+        self.symbols.insert(
+            name,
+            Symbol {
+                sym_type: SymbolType::Module { symbols },
+                index: 0,
+            },
+        );
+    }
+
+    /// Defines a name with a symbol from another module
+    pub fn def_symbol(&mut self, name: String, symbol: Symbol) {
+        self.symbols.insert(name, symbol);
+    }
+
+    /// Returns the names defined in this scope, not including its parents
+    pub fn symbols(&self) -> &HashMap<String, Symbol> {
+        &self.symbols
+    }
+
+    /// Defines a struct, the index is the struct's index in the module not a slot
+    pub fn def_struct(
+        &mut self,
+        name: String,
+        type_name: String,
+        index: usize,
+        fields: Vec<(String, Types)>,
+    ) {
         self.symbols
-            .insert(name, Symbol::new_fn(index, arg_types, return_types));
-        index
+            .insert(name, Symbol::new_struct(index, type_name, fields));
     }
 
+    /// Defines an enum and each of its variants, the index is the enum's index in the module
+    pub fn def_enum(
+        &mut self,
+        name: String,
+        type_name: String,
+        index: usize,
+        variants: Vec<(String, Vec<Types>)>,
+    ) {
+        for (variant_index, (variant, payload)) in variants.iter().enumerate() {
+            self.symbols.insert(
+                variant.clone(),
+                Symbol::new_variant(index, type_name.clone(), variant_index as u8, payload.clone()),
+            );
+        }
+
+        self.symbols
+            .insert(name, Symbol::new_enum(index, type_name, variants));
+    }
+
+    /// Defines a variable in a new slot, shadowing any earlier definition
     pub fn def_variable(&mut self, name: String, types: Types) -> u8 {
+        self.def_local(name, types, true)
+    }
+
+    /// Defines a variable that can not be reassigned in a new slot
+    pub fn def_const(&mut self, name: String, types: Types) -> u8 {
+        self.def_local(name, types, false)
+    }
+
+    fn def_local(&mut self, name: String, types: Types, mutable: bool) -> u8 {
         let index = self.get_next_index();
-        self.symbols.insert(name, Symbol::new_var(index, types));
+        self.slots += 1;
+        self.symbols
+            .insert(name, Symbol::new_var(index as usize, types, mutable));
         index
     }
 
+    /// Defines a module level variable, the index is the global's index in the module
+    pub fn def_global(&mut self, name: String, index: usize, types: Types, mutable: bool) {
+        self.symbols
+            .insert(name, Symbol::new_var(index, types, mutable));
+    }
+
+    /// Gives a variable more precise types for the rest of this scope
+    ///
+    /// The narrowed symbol shares the slot of the original variable. Globals
+    /// are never narrowed as any function call could change them.
+    pub fn narrow(&mut self, name: &String, types: Types) {
+        let (index, declared, mutable) = match self.resolve(name) {
+            (Some(symbol), false) => match symbol.get_type() {
+                SymbolType::Variable {
+                    declared, mutable, ..
+                } => (*symbol.get_index(), declared.clone(), *mutable),
+                _ => return,
+            },
+            _ => return,
+        };
+
+        self.symbols.insert(
+            name.clone(),
+            Symbol::new_narrowed(index, types, declared, mutable),
+        );
+    }
+
+    /// Gives a narrowed variable its declared types again for the rest of this scope
+    ///
+    /// Used once the variable is reassigned, as the narrowing may no longer hold.
+    pub fn widen(&mut self, name: &String) {
+        let declared = match self.resolve(name) {
+            (Some(symbol), false) => match symbol.get_type() {
+                SymbolType::Variable {
+                    types, declared, ..
+                } if types != declared => declared.clone(),
+                _ => return,
+            },
+            _ => return,
+        };
+
+        self.narrow(name, declared);
+    }
+
     /// Resolves a variable
     ///
     /// The second value is true if we resolved in the global scope
@@ -0,0 +1,406 @@
+use object::GearsObject;
+use std::collections::HashMap;
+
+/// The type of a value
+///
+/// A union is a `Types`, an optional type is a union with `None`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Type {
+    None,
+    Int,
+    Float,
+    Bool,
+    Str,
+    /// The element types are a union
+    List(Types),
+    /// The key types and the value types
+    Map(Types, Types),
+    /// Each item has its own union of types
+    Tuple(Vec<Types>),
+    /// The types of each arg and the return types
+    Function(Vec<Types>, Types),
+    /// A struct or an enum defined by the program
+    Named(String),
+    /// A type param of a generic function, replaced by the types it is
+    /// inferred as when the function is called
+    Param(String),
+}
+
+impl Type {
+    /// Creates a list type, the element types are a union
+    pub fn new_list(elements: Types) -> Type {
+        Type::List(normalize_types(elements))
+    }
+
+    /// Returns the element types if this is a list
+    pub fn list_elements(&self) -> Option<&Types> {
+        match self {
+            Type::List(elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    /// Creates a map type, the key and value types are each a union
+    pub fn new_map(keys: Types, values: Types) -> Type {
+        Type::Map(normalize_types(keys), normalize_types(values))
+    }
+
+    /// Returns the key and value types if this is a map
+    pub fn map_entries(&self) -> Option<(&Types, &Types)> {
+        match self {
+            Type::Map(keys, values) => Some((keys, values)),
+            _ => None,
+        }
+    }
+
+    /// Creates a tuple type, each item has its own union of types
+    pub fn new_tuple(items: Vec<Types>) -> Type {
+        Type::Tuple(items.into_iter().map(normalize_types).collect())
+    }
+
+    /// Returns the types of each item if this is a tuple
+    pub fn tuple_items(&self) -> Option<&Vec<Types>> {
+        match self {
+            Type::Tuple(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn new_function(args: Vec<Types>, returns: Types) -> Type {
+        Type::Function(
+            args.into_iter().map(normalize_types).collect(),
+            normalize_types(returns),
+        )
+    }
+
+    /// Returns the arg types and the return types of a function type
+    pub fn function_signature(&self) -> Option<(&[Types], &Types)> {
+        match self {
+            Type::Function(args, returns) => Some((args, returns)),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of a struct or enum type
+    pub fn named(&self) -> Option<&String> {
+        match self {
+            Type::Named(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of a type param
+    pub fn type_param(&self) -> Option<&String> {
+        match self {
+            Type::Param(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Returns the types with every type param replaced by the types it was inferred as
+    ///
+    /// Type params that were not inferred are kept.
+    pub fn substitute(&self, substitution: &Substitution) -> Types {
+        let res = match self {
+            Type::Param(name) => match substitution.get(name) {
+                Some(types) => return types.clone(),
+                None => self.clone(),
+            },
+            Type::List(elements) => Type::new_list(substitute_types(elements, substitution)),
+            Type::Map(keys, values) => Type::new_map(
+                substitute_types(keys, substitution),
+                substitute_types(values, substitution),
+            ),
+            Type::Tuple(items) => Type::new_tuple(
+                items
+                    .iter()
+                    .map(|item| substitute_types(item, substitution))
+                    .collect(),
+            ),
+            Type::Function(args, returns) => Type::new_function(
+                args.iter()
+                    .map(|arg| substitute_types(arg, substitution))
+                    .collect(),
+                substitute_types(returns, substitution),
+            ),
+            _ => self.clone(),
+        };
+
+        vec![res]
+    }
+
+    /// Checks if the type is the type param `name` or has it in it
+    pub fn has_type_param(&self, name: &str) -> bool {
+        let any = |types: &Types| types.iter().any(|t| t.has_type_param(name));
+
+        match self {
+            Type::Param(param) => param == name,
+            Type::List(elements) => any(elements),
+            Type::Map(keys, values) => any(keys) || any(values),
+            Type::Tuple(items) => items.iter().any(any),
+            Type::Function(args, returns) => args.iter().any(any) || any(returns),
+            _ => false,
+        }
+    }
+
+    /// Checks if a value of the type could be matched against this type
+    /// when inferring type params, containers match on their kind
+    fn same_shape(&self, other: &Type) -> bool {
+        match (self, other) {
+            (Type::List(_), Type::List(_)) | (Type::Map(..), Type::Map(..)) => true,
+            (Type::Tuple(items), Type::Tuple(other_items)) => items.len() == other_items.len(),
+            (Type::Function(args, _), Type::Function(other_args, _)) => {
+                args.len() == other_args.len()
+            }
+            _ => self == other,
+        }
+    }
+
+    /// Returns false if the type has an empty union in it, like the type of `[]`
+    ///
+    /// Values of these types can be used where a type is known, but a
+    /// variable can not be given the type.
+    pub fn is_complete(&self) -> bool {
+        let complete = |types: &Types| !types.is_empty() && types.iter().all(|t| t.is_complete());
+
+        match self {
+            Type::List(elements) => complete(elements),
+            Type::Map(keys, values) => complete(keys) && complete(values),
+            Type::Tuple(items) => items.iter().all(complete),
+            Type::Function(args, returns) => args.iter().all(complete) && complete(returns),
+            _ => true,
+        }
+    }
+
+    /// Only values of these types can be used as map keys
+    pub fn is_hashable(&self) -> bool {
+        match self {
+            Type::Int | Type::Str | Type::Bool => true,
+            _ => false,
+        }
+    }
+
+    /// Checks if a value of this type can be stored as one of the target types
+    ///
+    /// Lists and maps are compared on their contents, so `[int]` fits in
+    /// `[int | bool]` and the empty list `[]` fits in any list.
+    pub fn is_assignable_to(&self, targets: &Types) -> bool {
+        targets.iter().any(|target| match (self, target) {
+            (Type::List(elements), Type::List(target_elements)) => {
+                types_assignable(elements, target_elements)
+            }
+            (Type::Map(keys, values), Type::Map(target_keys, target_values)) => {
+                types_assignable(keys, target_keys) && types_assignable(values, target_values)
+            }
+            (Type::Tuple(items), Type::Tuple(target_items)) => {
+                items.len() == target_items.len()
+                    && items
+                        .iter()
+                        .zip(target_items)
+                        .all(|(item, target_item)| types_assignable(item, target_item))
+            }
+            // A function can be used in place of another if it accepts at
+            // least the same args and returns no more than the other returns
+            (Type::Function(args, returns), Type::Function(target_args, target_returns)) => {
+                args.len() == target_args.len()
+                    && args
+                        .iter()
+                        .zip(target_args)
+                        .all(|(arg, target_arg)| types_assignable(target_arg, arg))
+                    && types_assignable(returns, target_returns)
+            }
+            _ => self == target,
+        })
+    }
+
+    /// Checks if a runtime value is of this type
+    ///
+    /// Containers, structs and enums are checked item by item against the
+    /// declarations. The arg types of a function value are not known at
+    /// runtime, so only the number of args is checked.
+    pub fn accepts(&self, value: &GearsObject, declarations: &dyn Declarations) -> bool {
+        match (self, value) {
+            (Type::None, GearsObject::None)
+            | (Type::Int, GearsObject::Int(_))
+            | (Type::Float, GearsObject::Float(_))
+            | (Type::Bool, GearsObject::Bool(_))
+            | (Type::Str, GearsObject::Str(_)) => true,
+            (Type::Function(args, _), GearsObject::Function { index, .. }) => {
+                declarations.function_args(*index) == Some(args.len())
+            }
+            (Type::List(elements), GearsObject::List(items)) => items
+                .iter()
+                .all(|item| types_accept(elements, item, declarations)),
+            (Type::Map(keys, values), GearsObject::Map(entries)) => entries.iter().all(|(k, v)| {
+                types_accept(keys, k, declarations) && types_accept(values, v, declarations)
+            }),
+            (Type::Tuple(item_types), GearsObject::Tuple(items)) => {
+                items.len() == item_types.len()
+                    && items
+                        .iter()
+                        .zip(item_types)
+                        .all(|(item, types)| types_accept(types, item, declarations))
+            }
+            // Generics are erased, the value is only known by the caller
+            (Type::Param(_), _) => true,
+            (Type::Named(name), GearsObject::Struct { name: value, fields }) => {
+                let declared = match declarations.struct_fields(name) {
+                    Some(declared) if name == value => declared,
+                    _ => return false,
+                };
+
+                declared.len() == fields.len()
+                    && declared.iter().zip(fields).all(|((field, types), (value_field, value))| {
+                        *field == value_field && types_accept(types, value, declarations)
+                    })
+            }
+            (
+                Type::Named(name),
+                GearsObject::Enum {
+                    name: value,
+                    variant,
+                    values,
+                },
+            ) => {
+                let payload = match declarations.variant_payload(name, variant) {
+                    Some(payload) if name == value => payload,
+                    _ => return false,
+                };
+
+                payload.len() == values.len()
+                    && payload
+                        .iter()
+                        .zip(values)
+                        .all(|(types, value)| types_accept(types, value, declarations))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The user defined types and the functions of a module
+///
+/// Values from the host are checked against them before they are used.
+pub trait Declarations {
+    /// The fields of a struct and their types, in declaration order
+    fn struct_fields(&self, name: &str) -> Option<Vec<(&String, &Types)>>;
+
+    /// The types of the payload of a variant
+    fn variant_payload(&self, enum_name: &str, variant: &str) -> Option<&Vec<Types>>;
+
+    /// The number of args a function takes, None if there is no function at the index
+    fn function_args(&self, index: usize) -> Option<usize>;
+}
+
+pub type Types = Vec<Type>;
+
+/// Sorts and removes duplicates so equal unions compare as equal
+pub fn normalize_types(mut types: Types) -> Types {
+    types.sort();
+    types.dedup();
+    types
+}
+
+/// Checks if a runtime value is one of the types
+pub fn types_accept(types: &Types, value: &GearsObject, declarations: &dyn Declarations) -> bool {
+    types.iter().any(|t| t.accepts(value, declarations))
+}
+
+/// Checks if every type in `types` can be stored as one of the `targets`
+pub fn types_assignable(types: &Types, targets: &Types) -> bool {
+    types.iter().all(|t| t.is_assignable_to(targets))
+}
+
+/// Checks if the types are a union that includes none
+pub fn is_optional(types: &Types) -> bool {
+    types.contains(&Type::None)
+}
+
+/// Returns the types of an optional value once it is known not to be none
+pub fn without_none(types: &Types) -> Types {
+    types
+        .iter()
+        .filter(|t| **t != Type::None)
+        .cloned()
+        .collect()
+}
+
+/// Checks if the types are a single int or float
+pub fn is_numeric(types: &Types) -> bool {
+    match types.as_slice() {
+        [Type::Int] | [Type::Float] => true,
+        _ => false,
+    }
+}
+
+/// Returns the type of math on two numbers, mixing an int with a float
+/// promotes the result to a float
+pub fn numeric_result(left: &Types, right: &Types) -> Types {
+    if left.contains(&Type::Float) || right.contains(&Type::Float) {
+        vec![Type::Float]
+    } else {
+        vec![Type::Int]
+    }
+}
+
+/// The types each type param of a generic function was inferred as
+pub type Substitution = HashMap<String, Types>;
+
+/// Replaces every type param in the types with the types it was inferred as
+pub fn substitute_types(types: &Types, substitution: &Substitution) -> Types {
+    normalize_types(
+        types
+            .iter()
+            .flat_map(|t| t.substitute(substitution))
+            .collect(),
+    )
+}
+
+/// Infers the type params in `declared` from the types of the given value
+///
+/// A type param is inferred from the first value it is found in, later values
+/// are checked against it. Types of the value that match a type next to the
+/// type params in a union, like `none` in `T | none`, are not part of the params.
+pub fn infer_type_params(declared: &Types, given: &Types, substitution: &mut Substitution) {
+    let mut rest = Vec::new();
+
+    for t in given {
+        let matched = declared
+            .iter()
+            .find(|d| d.type_param().is_none() && d.same_shape(t));
+
+        match (matched, t) {
+            (Some(Type::List(elements)), Type::List(given_elements)) => {
+                infer_type_params(elements, given_elements, substitution)
+            }
+            (Some(Type::Map(keys, values)), Type::Map(given_keys, given_values)) => {
+                infer_type_params(keys, given_keys, substitution);
+                infer_type_params(values, given_values, substitution);
+            }
+            (Some(Type::Tuple(items)), Type::Tuple(given_items)) => {
+                for (item, given_item) in items.iter().zip(given_items) {
+                    infer_type_params(item, given_item, substitution);
+                }
+            }
+            (Some(Type::Function(args, returns)), Type::Function(given_args, given_returns)) => {
+                for (arg, given_arg) in args.iter().zip(given_args) {
+                    infer_type_params(arg, given_arg, substitution);
+                }
+
+                infer_type_params(returns, given_returns, substitution);
+            }
+            (Some(_), _) => {}
+            (None, _) => rest.push(t.clone()),
+        }
+    }
+
+    if rest.is_empty() {
+        return;
+    }
+
+    for name in declared.iter().filter_map(Type::type_param) {
+        substitution
+            .entry(name.clone())
+            .or_insert_with(|| normalize_types(rest.clone()));
+    }
+}
@@ -1,34 +1,60 @@
 use errors::{GearsError, InterOpErrorType};
-use module::{Function, Module};
-use object::{ArcGearsObject, ArcGearsResult, FALSE_OBJ, NONE_OBJ, TRUE_OBJ};
+use module::{Function, Module, Param};
+use object::{ArcGearsObject, ArcGearsResult, GearsObject, FALSE_OBJ, NONE_OBJ, TRUE_OBJ};
 use opcodes::*;
 use std::sync::Arc;
 
 /// Execute a function contained in a compiled module
+///
+/// Args that are left off the end use their default values.
 pub fn execute_function(
     module: &Module,
     function: &str,
-    args: Vec<ArcGearsObject>,
+    mut args: Vec<ArcGearsObject>,
 ) -> ArcGearsResult {
     let mod_fn = module.get_function(function)?;
     let num_given_args = args.len();
     let num_fn_args = mod_fn.num_args();
 
-    if num_given_args != num_fn_args {
-        if num_given_args < num_fn_args {
-            return Err(GearsError::InterOpError {
-                error: InterOpErrorType::TooFewArgs,
-                message: format!(
-                    "Function missing args: {}, expected: {}, received: {}",
-                    function, num_fn_args, num_given_args
-                ),
-            });
-        } else {
+    if num_given_args > num_fn_args {
+        return Err(GearsError::InterOpError {
+            error: InterOpErrorType::TooManyArgs,
+            message: format!(
+                "Function pass extra args: {}, expected: {}, received: {}",
+                function, num_fn_args, num_given_args
+            ),
+        });
+    }
+
+    while args.len() < num_fn_args {
+        match mod_fn.get_params().get(args.len()) {
+            Some(param) if param.get_default().is_some() => {
+                args.push(default_arg(module, param)?);
+            }
+            _ => {
+                return Err(GearsError::InterOpError {
+                    error: InterOpErrorType::TooFewArgs,
+                    message: format!(
+                        "Function missing args: {}, expected: {}, received: {}",
+                        function, num_fn_args, num_given_args
+                    ),
+                })
+            }
+        }
+    }
+
+    // The compiler checked every call in the module, values from the host
+    // are checked here so a wrong value fails before it reaches the function
+    for (param, arg) in mod_fn.get_params().iter().zip(&args) {
+        if !param.accepts(arg, module) {
             return Err(GearsError::InterOpError {
-                error: InterOpErrorType::TooManyArgs,
+                error: InterOpErrorType::WrongArgType,
                 message: format!(
-                    "Function pass extra args: {}, expected: {}, received: {}",
-                    function, num_fn_args, num_given_args
+                    "Function arg has the wrong type: {}, arg: {}, expected: {:?}, received: {}",
+                    function,
+                    param.get_name(),
+                    param.get_types(),
+                    arg.get_type_str()
                 ),
             });
         }
@@ -37,6 +63,102 @@ pub fn execute_function(
     execute(&mod_fn, &module, args)
 }
 
+/// Execute a function with args given by name, args that are left out use their default values
+pub fn execute_function_named(
+    module: &Module,
+    function: &str,
+    args: Vec<(&str, ArcGearsObject)>,
+) -> ArcGearsResult {
+    let mod_fn = module.get_function(function)?;
+    let params = mod_fn.get_params();
+    let mut values: Vec<Option<ArcGearsObject>> = vec![None; params.len()];
+
+    for (name, value) in args {
+        match params.iter().position(|param| param.get_name() == name) {
+            Some(index) if values[index].is_none() => values[index] = Some(value),
+            Some(_) => {
+                return Err(GearsError::InterOpError {
+                    error: InterOpErrorType::DuplicateArg,
+                    message: format!("Function arg given more than once: {}, arg: {}", function, name),
+                })
+            }
+            None => {
+                return Err(GearsError::InterOpError {
+                    error: InterOpErrorType::UnknownArg,
+                    message: format!("Function has no arg: {}, arg: {}", function, name),
+                })
+            }
+        }
+    }
+
+    let mut positional = Vec::new();
+
+    for (param, value) in params.iter().zip(values) {
+        match value {
+            Some(value) => positional.push(value),
+            None if param.get_default().is_some() => positional.push(default_arg(module, param)?),
+            None => {
+                return Err(GearsError::InterOpError {
+                    error: InterOpErrorType::TooFewArgs,
+                    message: format!(
+                        "Function missing arg: {}, arg: {}",
+                        function,
+                        param.get_name()
+                    ),
+                })
+            }
+        }
+    }
+
+    execute_function(module, function, positional)
+}
+
+/// Computes the default value of a param
+fn default_arg(module: &Module, param: &Param) -> ArcGearsResult {
+    match param.get_default() {
+        Some(index) => execute(module.get_function_by_index(index)?, module, Vec::new()),
+        None => Err(GearsError::InternalCompilerError(format!(
+            "{} has no default value",
+            param.get_name()
+        ))),
+    }
+}
+
+/// Runs the initializer of a module, setting its globals
+///
+/// Compiling a module runs this once, running it again resets the globals.
+pub fn initialize_module(module: &Module) -> Result<(), GearsError> {
+    if let Some(initializer) = module.get_initializer() {
+        execute(initializer, module, Vec::new())?;
+    }
+
+    Ok(())
+}
+
+/// Runs a function called from the bytecode with `arg_count` args
+///
+/// The compiler checks the args of every call, a different count means the
+/// bytecode is wrong. The args are followed by any captured values.
+fn call_function(
+    module: &Module,
+    index: usize,
+    arg_count: usize,
+    args: Vec<ArcGearsObject>,
+) -> ArcGearsResult {
+    let function = module.get_function_by_index(index)?;
+
+    if arg_count != function.num_args() {
+        return Err(GearsError::InternalCompilerError(format!(
+            "{} takes {} args, {} were given",
+            function.get_name(),
+            function.num_args(),
+            arg_count
+        )));
+    }
+
+    execute(function, module, args)
+}
+
 fn execute(function: &Function, module: &Module, mut args: Vec<ArcGearsObject>) -> ArcGearsResult {
     let opcodes = function.get_opcodes();
     let mut cur_instr: u8;
@@ -84,6 +206,16 @@ fn execute(function: &Function, module: &Module, mut args: Vec<ArcGearsObject>)
         }};
     }
 
+    // Jump offsets and the indexes of module items are stored in two bytes,
+    // the high byte first
+    macro_rules! read_u16 {
+        () => {{
+            let value = (opcodes[ip] as usize) << 8 | opcodes[ip + 1] as usize;
+            ip += 2;
+            value
+        }};
+    }
+
     loop {
         advance!();
 
@@ -96,6 +228,14 @@ fn execute(function: &Function, module: &Module, mut args: Vec<ArcGearsObject>)
             BIN_SUB => bin_op!(sub),
             BIN_MUL => bin_op!(mul),
             BIN_DIV => bin_op!(div),
+            BIN_FLOOR_DIV => bin_op!(floor_div),
+            BIN_MOD => bin_op!(modulo),
+            BIN_POW => bin_op!(pow),
+            BIN_IN => {
+                let container: ArcGearsObject = pop!();
+                let item: ArcGearsObject = pop!();
+                push!(Arc::new(container.contains(&item)?));
+            }
 
             BIN_EQUAL => bin_op!(equal),
             BIN_NOT_EQUAL => bin_op!(nequal),
@@ -120,19 +260,231 @@ fn execute(function: &Function, module: &Module, mut args: Vec<ArcGearsObject>)
                 advance!();
                 let index = cur_instr as usize;
 
-                if args.len() > index {
-                    args[index] = pop!();
-                } else {
-                    args.insert(index, pop!());
+                if args.len() <= index {
+                    args.resize(index + 1, NONE_OBJ.clone());
                 }
+
+                args[index] = pop!();
+            }
+            LOAD_GLOBAL => {
+                let index = read_u16!();
+                push!(module.load_global(index)?);
+            }
+            STORE_GLOBAL => {
+                let index = read_u16!();
+                module.store_global(index, pop!())?;
             }
             LOAD_CONST => {
+                let index = read_u16!();
+                push!(module.get_const(index).clone());
+            }
+            BUILD_LIST => {
+                advance!();
+
+                let mut items = Vec::new();
+
+                for _ in 0..cur_instr {
+                    items.push(pop!());
+                }
+                items.reverse();
+
+                push!(Arc::new(GearsObject::List(items)));
+            }
+            BUILD_TUPLE => {
+                advance!();
+
+                let mut items = Vec::new();
+
+                for _ in 0..cur_instr {
+                    items.push(pop!());
+                }
+                items.reverse();
+
+                push!(Arc::new(GearsObject::Tuple(items)));
+            }
+            UNPACK_TUPLE => {
+                advance!();
+                let tuple: ArcGearsObject = pop!();
+
+                for item in tuple.unpack(cur_instr as usize)?.into_iter().rev() {
+                    push!(item);
+                }
+            }
+            BUILD_STRUCT => {
+                let struct_def = module.get_struct_by_index(read_u16!())?;
+
+                let mut values = Vec::new();
+
+                for _ in struct_def.get_fields() {
+                    values.push(pop!());
+                }
+                values.reverse();
+
+                push!(Arc::new(GearsObject::Struct {
+                    name: struct_def.get_name().to_string(),
+                    fields: struct_def.get_fields().iter().cloned().zip(values).collect(),
+                }));
+            }
+            GET_FIELD => {
+                let field = module.get_const(read_u16!());
+                let target: ArcGearsObject = pop!();
+
+                match *field {
+                    GearsObject::Str(ref field) => push!(target.get_field(field)?),
+                    _ => {
+                        return Err(GearsError::InternalCompilerError(
+                            "GET_FIELD expects a string const".to_string(),
+                        ))
+                    }
+                }
+            }
+            SET_FIELD => {
+                let field = module.get_const(read_u16!());
+                let value: ArcGearsObject = pop!();
+                let target: ArcGearsObject = pop!();
+
+                match *field {
+                    GearsObject::Str(ref field) => push!(Arc::new(target.set_field(field, value)?)),
+                    _ => {
+                        return Err(GearsError::InternalCompilerError(
+                            "SET_FIELD expects a string const".to_string(),
+                        ))
+                    }
+                }
+            }
+            BUILD_VARIANT => {
+                let enum_def = module.get_enum_by_index(read_u16!())?;
+                advance!();
+
+                let (variant, count) = match enum_def.get_variants().get(cur_instr as usize) {
+                    Some(v) => v,
+                    None => {
+                        return Err(GearsError::InternalCompilerError(
+                            "BUILD_VARIANT got an unknown variant".to_string(),
+                        ))
+                    }
+                };
+
+                let mut values = Vec::new();
+
+                for _ in 0..*count {
+                    values.push(pop!());
+                }
+                values.reverse();
+
+                push!(Arc::new(GearsObject::Enum {
+                    name: enum_def.get_name().to_string(),
+                    variant: variant.clone(),
+                    values,
+                }));
+            }
+            IS_VARIANT => {
+                let enum_def = module.get_enum_by_index(read_u16!())?;
+                advance!();
+                let target: ArcGearsObject = pop!();
+
+                match enum_def.get_variants().get(cur_instr as usize) {
+                    Some((variant, _)) => push!(if target.is_variant(enum_def.get_name(), variant) {
+                        TRUE_OBJ.clone()
+                    } else {
+                        FALSE_OBJ.clone()
+                    }),
+                    None => {
+                        return Err(GearsError::InternalCompilerError(
+                            "IS_VARIANT got an unknown variant".to_string(),
+                        ))
+                    }
+                }
+            }
+            GET_PAYLOAD => {
                 advance!();
-                push!(module.get_const(cur_instr as usize).clone());
+                let target: ArcGearsObject = pop!();
+                push!(target.payload(cur_instr as usize)?);
+            }
+            BUILD_MAP => {
+                advance!();
+
+                let mut items = Vec::new();
+
+                for _ in 0..cur_instr {
+                    let value = pop!();
+                    let key = pop!();
+                    items.push((key, value));
+                }
+                items.reverse();
+
+                push!(Arc::new(GearsObject::new_map(items)?));
+            }
+            RANGE_STEP => bin_op!(range_step),
+            RANGE_NEXT => bin_op!(range_next),
+            RANGE_CHECK => {
+                let step: ArcGearsObject = pop!();
+                let end: ArcGearsObject = pop!();
+                let current: ArcGearsObject = pop!();
+                push!(Arc::new(current.range_check(&end, &step)?));
+            }
+            GET_ITER => unary_op!(iter),
+            LEN => unary_op!(len),
+            POP_TOP => {
+                pop!();
+            }
+            DUP_TOP => {
+                advance!();
+
+                let count = cur_instr as usize;
+                if count > stack.len() {
+                    return Err(GearsError::InternalCompilerError(
+                        "Unexpected Empty Stack".to_string(),
+                    ));
+                }
+
+                let top = stack[stack.len() - count..].to_vec();
+                stack.extend(top);
+            }
+            INDEX => {
+                let index: ArcGearsObject = pop!();
+                let target: ArcGearsObject = pop!();
+                push!(target.index(&index)?);
+            }
+            STORE_INDEX => {
+                let value: ArcGearsObject = pop!();
+                let index: ArcGearsObject = pop!();
+                let target: ArcGearsObject = pop!();
+                push!(Arc::new(target.store_index(index, value)?));
+            }
+            SLICE => {
+                let end: ArcGearsObject = pop!();
+                let start: ArcGearsObject = pop!();
+                let target: ArcGearsObject = pop!();
+                push!(Arc::new(target.slice(&start, &end)?));
             }
             CALL_FUNCTION => {
+                let fn_index = read_u16!();
+                advance!();
+
+                let mut next_args = Vec::new();
+
+                for _ in 0..cur_instr {
+                    next_args.push(pop!());
+                }
+                next_args.reverse();
+
+                push!(call_function(module, fn_index, cur_instr as usize, next_args)?);
+            }
+            LOAD_FUNCTION => {
+                let index = read_u16!();
                 advance!();
-                let fn_index = cur_instr;
+
+                let mut captures = Vec::new();
+
+                for _ in 0..cur_instr {
+                    captures.push(pop!());
+                }
+                captures.reverse();
+
+                push!(Arc::new(GearsObject::Function { index, captures }));
+            }
+            CALL_INDIRECT => {
                 advance!();
 
                 let mut next_args = Vec::new();
@@ -142,11 +494,25 @@ fn execute(function: &Function, module: &Module, mut args: Vec<ArcGearsObject>)
                 }
                 next_args.reverse();
 
-                push!(execute(
-                    module.get_function_by_index(fn_index as usize)?,
-                    module,
-                    next_args
-                )?);
+                // Captured values are stored in the slots after the args
+                let target: ArcGearsObject = pop!();
+                let index = match *target {
+                    GearsObject::Function {
+                        index,
+                        ref captures,
+                    } => {
+                        next_args.extend(captures.iter().cloned());
+                        index
+                    }
+                    _ => {
+                        return Err(GearsError::TypeError(format!(
+                            "{} is not callable",
+                            target.get_type_str()
+                        )))
+                    }
+                };
+
+                push!(call_function(module, index, cur_instr as usize, next_args)?);
             }
             LOAD_TRUE => {
                 push!(TRUE_OBJ.clone());
@@ -158,22 +524,36 @@ fn execute(function: &Function, module: &Module, mut args: Vec<ArcGearsObject>)
                 push!(NONE_OBJ.clone());
             }
             JUMP => {
-                advance!();
-                ip += cur_instr as usize;
+                let offset = read_u16!();
+                ip += offset;
             }
             JUMP_ABSOLUTE => {
-                cur_instr = opcodes[ip];
-                ip = cur_instr as usize;
+                ip = (opcodes[ip] as usize) << 8 | opcodes[ip + 1] as usize;
             }
             JUMP_IF_FALSE => {
-                advance!();
+                let offset = read_u16!();
                 if !pop!().as_bool() {
-                    ip += cur_instr as usize;
+                    ip += offset;
+                }
+            }
+            JUMP_IF_TRUE => {
+                let offset = read_u16!();
+                if pop!().as_bool() {
+                    ip += offset;
                 }
             }
             INC_ONE => {
                 unary_op!(inc);
             }
+            UNARY_NOT => {
+                unary_op!(not);
+            }
+            UNARY_NEG => {
+                unary_op!(neg);
+            }
+            TO_STR => {
+                unary_op!(to_str);
+            }
             _ => {
                 return Err(GearsError::InternalCompilerError(format!(
                     "Unexpected Opcode: {:?}",
@@ -188,17 +568,16 @@ fn execute(function: &Function, module: &Module, mut args: Vec<ArcGearsObject>)
 mod tests {
     use super::*;
     use module::ModuleBuilder;
-    use object::GearsObject;
 
     #[test]
     fn test_addition() {
         let mut module_builder = ModuleBuilder::new("Test".to_string());
 
         module_builder.start_function("simple_math".to_string(), 0);
-        module_builder.load_int(3);
-        module_builder.load_int(4);
+        module_builder.load_int(3).unwrap();
+        module_builder.load_int(4).unwrap();
         module_builder.op_add();
-        module_builder.load_int(8);
+        module_builder.load_int(8).unwrap();
         module_builder.op_add();
         module_builder.finish_function();
 
@@ -207,15 +586,36 @@ mod tests {
         assert_eq!(result, Ok(Arc::new(GearsObject::Int(15))));
     }
 
+    #[test]
+    fn test_dup_top() {
+        let mut module_builder = ModuleBuilder::new("Test".to_string());
+
+        // 20 - (4 - 2 * (20 - 4))
+        module_builder.start_function("dup".to_string(), 0);
+        module_builder.load_int(20).unwrap();
+        module_builder.load_int(4).unwrap();
+        module_builder.dup_top(2);
+        module_builder.op_sub();
+        module_builder.dup_top(1);
+        module_builder.op_add();
+        module_builder.op_sub();
+        module_builder.op_sub();
+        module_builder.finish_function();
+
+        let module = module_builder.build();
+        let result = execute_function(&module, "dup", Vec::new());
+        assert_eq!(result, Ok(Arc::new(GearsObject::Int(48))));
+    }
+
     #[test]
     fn test_subtraction() {
         let mut module_builder = ModuleBuilder::new("Test".to_string());
 
         module_builder.start_function("simple_math".to_string(), 0);
-        module_builder.load_int(20);
-        module_builder.load_int(4);
+        module_builder.load_int(20).unwrap();
+        module_builder.load_int(4).unwrap();
         module_builder.op_sub();
-        module_builder.load_int(5);
+        module_builder.load_int(5).unwrap();
         module_builder.op_sub();
         module_builder.finish_function();
 
@@ -229,10 +629,10 @@ mod tests {
         let mut module_builder = ModuleBuilder::new("Test".to_string());
 
         module_builder.start_function("simple_math".to_string(), 0);
-        module_builder.load_int(3);
-        module_builder.load_int(4);
+        module_builder.load_int(3).unwrap();
+        module_builder.load_int(4).unwrap();
         module_builder.op_mul();
-        module_builder.load_int(5);
+        module_builder.load_int(5).unwrap();
         module_builder.op_mul();
         module_builder.finish_function();
 
@@ -246,10 +646,10 @@ mod tests {
         let mut module_builder = ModuleBuilder::new("Test".to_string());
 
         module_builder.start_function("simple_math".to_string(), 0);
-        module_builder.load_int(50);
-        module_builder.load_int(5);
+        module_builder.load_int(50).unwrap();
+        module_builder.load_int(5).unwrap();
         module_builder.op_div();
-        module_builder.load_int(5);
+        module_builder.load_int(5).unwrap();
         module_builder.op_div();
         module_builder.finish_function();
 
@@ -257,4 +657,83 @@ mod tests {
         let result = execute_function(&module, "simple_math", Vec::new());
         assert_eq!(result, Ok(Arc::new(GearsObject::Int(2))));
     }
+
+    #[test]
+    fn test_jump_limit() {
+        let mut module_builder = ModuleBuilder::new("Test".to_string());
+
+        module_builder.start_function("long_jump".to_string(), 0);
+        let index = module_builder.start_jump();
+        for _ in 0..u16::max_value() {
+            module_builder.load_none();
+        }
+        assert!(module_builder.end_jump(index).is_ok());
+
+        let index = module_builder.start_jump();
+        for _ in 0..=u16::max_value() {
+            module_builder.load_none();
+        }
+        match module_builder.end_jump(index) {
+            Err(GearsError::LimitError(_)) => {}
+            result => panic!("Wrong result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_index_limit() {
+        let mut module_builder = ModuleBuilder::new("Test".to_string());
+
+        module_builder.start_function("far_call".to_string(), 0);
+        assert!(module_builder.call_fn(u16::max_value() as usize, 0).is_ok());
+
+        match module_builder.call_fn(u16::max_value() as usize + 1, 0) {
+            Err(GearsError::LimitError(_)) => {}
+            result => panic!("Wrong result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_call_arity() {
+        let mut module_builder = ModuleBuilder::new("Test".to_string());
+
+        module_builder.start_function("double".to_string(), 1);
+        module_builder.load_fast(0);
+        module_builder.load_fast(0);
+        module_builder.op_add();
+        let double = module_builder.finish_function();
+
+        module_builder.start_function("right_args".to_string(), 0);
+        module_builder.load_int(4).unwrap();
+        module_builder.call_fn(double, 1).unwrap();
+        module_builder.finish_function();
+
+        module_builder.start_function("missing_arg".to_string(), 0);
+        module_builder.call_fn(double, 0).unwrap();
+        module_builder.finish_function();
+
+        module_builder.start_function("extra_arg".to_string(), 0);
+        module_builder.load_function(double, 0).unwrap();
+        module_builder.load_int(4).unwrap();
+        module_builder.load_int(5).unwrap();
+        module_builder.call_indirect(2);
+        module_builder.finish_function();
+
+        let module = module_builder.build();
+        assert_eq!(
+            execute_function(&module, "right_args", Vec::new()),
+            Ok(Arc::new(GearsObject::Int(8)))
+        );
+        assert_eq!(
+            execute_function(&module, "missing_arg", Vec::new()),
+            Err(GearsError::InternalCompilerError(
+                "double takes 1 args, 0 were given".to_string()
+            ))
+        );
+        assert_eq!(
+            execute_function(&module, "extra_arg", Vec::new()),
+            Err(GearsError::InternalCompilerError(
+                "double takes 1 args, 2 were given".to_string()
+            ))
+        );
+    }
 }
@@ -21,3 +21,36 @@ def reassign() -> int {
 
     a
 }
+
+def inferred(a: int, b: int) -> int {
+    let total = a + b;
+    let items = [total, 2];
+    let (first, second) = (items[0], "unused");
+    first * 2
+}
+
+def inferred_union(flag: bool) -> int | str {
+    let value = if flag { 1 } else { "one" };
+    value
+}
+
+def inferred_function() -> int {
+    let double = def(x: int) -> int { x * 2 };
+    double(21)
+}
+
+def constants(count: int) -> int {
+    const size = 3;
+    const (low, high) = (1, 10);
+    let total = low;
+    let last: int | none = none;
+
+    for i in 0 to count {
+        count = count - 1;
+        total = total + size;
+        last = i;
+    };
+
+    let add = def(x: int) -> int { x + high };
+    add(total)
+}
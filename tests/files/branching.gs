@@ -37,3 +37,227 @@ def for_loop() -> int {
     };
     result
 }
+
+def expr_range(a: int, b: int) -> int {
+    let result: int = 0;
+
+    for x in a * 2 to b + 1 {
+        result = result + x;
+    };
+    result
+}
+
+def step_range(size: int) -> int {
+    let result: int = 0;
+
+    for x in 0 to 10 step size {
+        result = result * 100 + x;
+    };
+    result
+}
+
+def range_near_limits() -> int {
+    let result: int = 0;
+
+    for x in 9223372036854775800 to 9223372036854775807 step 5 {
+        result = result * 10 + (x - 9223372036854775800);
+    };
+    for x in -9223372036854775800 to -9223372036854775808 step -5 {
+        result = result * 10 + (-9223372036854775800 - x);
+    };
+    result
+}
+
+def descending_range() -> int {
+    let result: int = 0;
+
+    for x in 3 to -1 {
+        result = result * 100 + x;
+    };
+    result
+}
+
+def sum_list(items: [int]) -> int {
+    let result: int = 0;
+
+    for item in items {
+        result = result + item;
+    };
+    result
+}
+
+def count_letter(s: str, letter: str) -> int {
+    let result: int = 0;
+
+    for c in s {
+        if c == letter {
+            result = result + 1;
+        };
+    };
+    result
+}
+
+def nested_loops() -> int {
+    let result: int = 0;
+
+    for row in [[1, 2], [3], []] {
+        for item in row {
+            for x in 0 to item {
+                result = result + 1;
+            };
+        };
+    };
+    result
+}
+
+def while_break() -> int {
+    let a: int = 0;
+    while true {
+        a = a + 1;
+        if a == 7 {
+            break;
+        };
+    };
+    a
+}
+
+def for_continue() -> int {
+    let result: int = 0;
+
+    for x in 0 to 10 {
+        if x % 2 == 0 {
+            continue;
+        };
+        result = result + x;
+    };
+    result
+}
+
+def while_continue() -> int {
+    let a: int = 0;
+    let result: int = 0;
+    while a < 10 {
+        a = a + 1;
+        if a > 3 and a < 8 {
+            continue
+        };
+        result = result + a;
+    };
+    result
+}
+
+def nested_break(items: [[int]]) -> int {
+    let result: int = 0;
+
+    for row in items {
+        for item in row {
+            if item < 0 {
+                break;
+            };
+            result = result + item;
+        };
+        if result > 100 {
+            break;
+        };
+    };
+    result
+}
+
+def add_or_zero(a: int | none, b: int) -> int {
+    if a != none {
+        a + b
+    } else {
+        0
+    }
+}
+
+def default_to(a: int?, default: int) -> int {
+    if a == none {
+        return default;
+    };
+    a * 2
+}
+
+def both_set(a: int | none, b: int | none) -> bool {
+    a != none and b != none and a + b > 10
+}
+
+def match_optional(a: int | none) -> int {
+    match a {
+        none => -1,
+        0 => 0,
+        _ => a + 1,
+    }
+}
+
+def clear_if_set(a: int | none) -> int | none {
+    if a != none {
+        a = none;
+    };
+    a
+}
+
+def clear_after_guard(a: int?) -> int {
+    if a == none {
+        return 0;
+    };
+    let doubled = a * 2;
+    a = none;
+    if a == none {
+        doubled
+    } else {
+        -1
+    }
+}
+
+def late_loop() -> int {
+    let a0: int = 0 * 0 + 0;
+    let a1: int = 1 * 1 + 1;
+    let a2: int = 2 * 2 + 2;
+    let a3: int = 3 * 3 + 3;
+    let a4: int = 4 * 4 + 4;
+    let a5: int = 5 * 5 + 5;
+    let a6: int = 6 * 6 + 6;
+    let a7: int = 7 * 7 + 7;
+    let a8: int = 8 * 8 + 8;
+    let a9: int = 9 * 9 + 9;
+    let a10: int = 10 * 10 + 10;
+    let a11: int = 11 * 11 + 11;
+    let a12: int = 12 * 12 + 12;
+    let a13: int = 13 * 13 + 13;
+    let a14: int = 14 * 14 + 14;
+    let a15: int = 15 * 15 + 15;
+    let a16: int = 16 * 16 + 16;
+    let a17: int = 17 * 17 + 17;
+    let a18: int = 18 * 18 + 18;
+    let a19: int = 19 * 19 + 19;
+    let a20: int = 20 * 20 + 20;
+    let a21: int = 21 * 21 + 21;
+    let a22: int = 22 * 22 + 22;
+    let a23: int = 23 * 23 + 23;
+    let a24: int = 24 * 24 + 24;
+    let a25: int = 25 * 25 + 25;
+    let a26: int = 26 * 26 + 26;
+    let a27: int = 27 * 27 + 27;
+    let a28: int = 28 * 28 + 28;
+    let a29: int = 29 * 29 + 29;
+    let a30: int = 30 * 30 + 30;
+    let a31: int = 31 * 31 + 31;
+    let a32: int = 32 * 32 + 32;
+    let a33: int = 33 * 33 + 33;
+    let a34: int = 34 * 34 + 34;
+    let a35: int = 35 * 35 + 35;
+    let a36: int = 36 * 36 + 36;
+    let a37: int = 37 * 37 + 37;
+    let a38: int = 38 * 38 + 38;
+    let a39: int = 39 * 39 + 39;
+    let result: int = a39;
+
+    for x in 0 to 3 {
+        result = result + x;
+    };
+    while result < 1600 {
+        result = result + 1;
+    };
+    result
+}
@@ -0,0 +1,65 @@
+def double(x: int) -> int {
+    x * 2
+}
+
+def apply(f: (int) -> int, x: int) -> int {
+    f(x)
+}
+
+def apply_named(x: int) -> int {
+    apply(double, x)
+}
+
+def make_adder(n: int) -> (int) -> int {
+    def(x: int) -> int {
+        x + n
+    }
+}
+
+def use_adder(x: int) -> int {
+    let add_five: (int) -> int = make_adder(5);
+    add_five(x)
+}
+
+def call_result(a: int, b: int) -> int {
+    make_adder(a)(b)
+}
+
+def count_if(items: [int], keep: (int) -> bool) -> int {
+    let count: int = 0;
+
+    for item in items {
+        if keep(item) {
+            count = count + 1;
+        };
+    };
+    count
+}
+
+def count_above(items: [int], limit: int) -> int {
+    count_if(items, def(item: int) -> bool { item > limit })
+}
+
+def captured_by_value() -> int {
+    let n: int = 1;
+    let get: () -> int = def() -> int { n };
+    n = 5;
+    get() + n
+}
+
+def nested_capture(a: int, b: int) -> int {
+    let outer: (int) -> int = def(x: int) -> int {
+        let inner: () -> int = def() -> int { x * a };
+        inner() + b
+    };
+    outer(10)
+}
+
+def function_list(x: int) -> int {
+    let fns: [(int) -> int] = [double, make_adder(3)];
+    fns[0](x) + fns[1](x)
+}
+
+def widen(f: (int | none) -> int) -> (int) -> int {
+    f
+}
@@ -0,0 +1,147 @@
+enum Status {
+    Pending,
+    Approved(int),
+    Rejected(str),
+}
+
+enum Shape {
+    Circle(int),
+    Rect(int, int),
+}
+
+enum Wrap {
+    Value(int?),
+    Empty,
+}
+
+def approve(amount: int) -> Status {
+    Approved(amount)
+}
+
+def pending() -> Status {
+    Pending
+}
+
+def describe(s: Status) -> str {
+    match s {
+        Pending => "pending",
+        Approved(amount) => "approved",
+        Rejected(reason) => reason,
+    }
+}
+
+def amount(s: Status) -> int {
+    match s {
+        Approved(0) => -1,
+        Approved(n) => {
+            n * 2
+        },
+        _ => 0,
+    }
+}
+
+def area(s: Shape) -> int {
+    match s {
+        Circle(r) => 3 * r * r,
+        Rect(w, h) => w * h,
+    }
+}
+
+def is_square(s: Shape) -> bool {
+    match s {
+        Rect(w, h) => w == h,
+        Circle(_) => false,
+    }
+}
+
+def name_of(n: int) -> str {
+    match n {
+        1 => "one",
+        2 => "two",
+        -1 => "minus one",
+        other => "many",
+    }
+}
+
+def flip(b: bool) -> bool {
+    match b {
+        true => false,
+        false => true,
+    }
+}
+
+def nested(s: Status | none) -> int {
+    match s {
+        Approved(n) => n,
+        _ => 0,
+    }
+}
+
+def unwrap_or(w: Wrap, d: int) -> int {
+    match w {
+        Value(none) => d,
+        Value(n) => n,
+        Empty => d,
+    }
+}
+
+def big_match(n: int) -> int {
+    match n {
+        0 => {
+            let a: int = n * 1;
+            let b: int = a + 0;
+            b - n
+        },
+        1 => {
+            let a: int = n * 2;
+            let b: int = a + 1;
+            b - n
+        },
+        2 => {
+            let a: int = n * 3;
+            let b: int = a + 2;
+            b - n
+        },
+        3 => {
+            let a: int = n * 4;
+            let b: int = a + 3;
+            b - n
+        },
+        4 => {
+            let a: int = n * 5;
+            let b: int = a + 4;
+            b - n
+        },
+        5 => {
+            let a: int = n * 6;
+            let b: int = a + 5;
+            b - n
+        },
+        6 => {
+            let a: int = n * 7;
+            let b: int = a + 6;
+            b - n
+        },
+        7 => {
+            let a: int = n * 8;
+            let b: int = a + 7;
+            b - n
+        },
+        8 => {
+            let a: int = n * 9;
+            let b: int = a + 8;
+            b - n
+        },
+        9 => {
+            let a: int = n * 10;
+            let b: int = a + 9;
+            b - n
+        },
+        10 => {
+            let a: int = n * 11;
+            let b: int = a + 10;
+            b - n
+        },
+        _ => 0,
+    }
+}
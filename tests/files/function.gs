@@ -32,3 +32,103 @@ def add(a: int, b: int) -> int {
 def add_two(a: int) -> int {
     add(a, 2)
 }
+
+def early_return(a: int) -> int {
+    if a < 0 {
+        return 0;
+    };
+    a * 2
+}
+
+def return_in_loop(items: [int], target: int) -> int | none {
+    let index: int = 0;
+
+    for item in items {
+        if item == target {
+            return index;
+        };
+        index = index + 1;
+    };
+    return;
+}
+
+def branches_return(a: bool) -> str {
+    if a {
+        return "yes";
+    } else {
+        return "no";
+    }
+}
+
+def nested_return(a: int) -> int {
+    let count: int = 0;
+
+    while count < 10 {
+        for x in 0 to 10 {
+            if x == a {
+                return x * 10
+            }
+        };
+        count = count + 1;
+    };
+    -1
+}
+
+def scale(a: int, factor: int = 10, offset: int = 0) -> int {
+    a * factor + offset
+}
+
+def scale_defaults(a: int) -> int {
+    scale(a)
+}
+
+def scale_keywords(a: int) -> int {
+    scale(a, offset: 3) + scale(factor: 2, a: a)
+}
+
+def greet(name: str = "world", punctuation: str = "!") -> str {
+    f"hello {name}{punctuation}"
+}
+
+def greet_keyword() -> str {
+    greet(punctuation: "?")
+}
+
+def with_default(items: [int] = [1, 2]) -> int {
+    let total: int = 0;
+
+    for item in items {
+        total = total + item;
+    };
+    total
+}
+
+let trace: int = 0;
+
+struct Pair {
+    first: int,
+    second: int,
+}
+
+def record(digit: int) -> int {
+    trace = trace * 10 + digit;
+    digit
+}
+
+def keyword_order() -> int {
+    trace = 0;
+    let value: int = scale(offset: record(3), a: record(1), factor: record(2));
+    trace * 1000 + value
+}
+
+def keyword_order_defaults() -> int {
+    trace = 0;
+    let value: int = scale(offset: record(3), a: record(1));
+    trace * 1000 + value
+}
+
+def field_order() -> int {
+    trace = 0;
+    let pair: Pair = Pair(second: record(2), first: record(1));
+    trace * 100 + pair.first * 10 + pair.second
+}
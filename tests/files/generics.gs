@@ -0,0 +1,57 @@
+def first<T>(xs: [T]) -> T {
+    xs[0]
+}
+
+def first_or<T>(xs: [T], default: T) -> T {
+    for x in xs {
+        return x;
+    };
+    default
+}
+
+def or_default<T>(x: T | none, default: T) -> T {
+    if x == none {
+        default
+    } else {
+        x
+    }
+}
+
+def swap<A, B>(pair: (A, B)) -> (B, A) {
+    let (a, b) = pair;
+    (b, a)
+}
+
+def apply_twice<T>(f: (T) -> T, x: T) -> T {
+    let once: T = f(x);
+    f(once)
+}
+
+def nested_first<T>(xs: [[T]]) -> T {
+    first(first(xs))
+}
+
+def double(x: int) -> int {
+    x * 2
+}
+
+def use_first() -> int {
+    first([4, 5]) + first_or([], 3)
+}
+
+def use_or_default(flag: bool) -> int {
+    let value: int | none = if flag { 4 } else { none };
+    or_default(value, 10) + or_default(none, 100)
+}
+
+def use_swap() -> (str, int) {
+    swap((1, "one"))
+}
+
+def use_apply_twice() -> int {
+    apply_twice(double, 5)
+}
+
+def use_nested_first() -> str {
+    nested_first([["a", "b"], ["c"]])
+}
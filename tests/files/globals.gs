@@ -0,0 +1,51 @@
+const RATE: int = 5;
+const NAMES: [str] = ["a", "b"];
+let counter: int = 0;
+let total: int = RATE * 2;
+let last: str | none = none;
+
+def apply_rate(x: int) -> int {
+    x * RATE
+}
+
+def get_total() -> int {
+    total
+}
+
+def increment() -> int {
+    counter = counter + 1;
+    counter
+}
+
+def remember(name: str) {
+    last = name;
+}
+
+def last_name() -> str {
+    let name: str | none = last;
+
+    if name == none {
+        return "nobody";
+    };
+
+    name
+}
+
+def rate_adder() -> (int) -> int {
+    def(x: int) -> int {
+        x + RATE
+    }
+}
+
+def add_rate(x: int) -> int {
+    let add: (int) -> int = rate_adder();
+    add(x)
+}
+
+def joined_names() -> str {
+    let result: str = "";
+    for name in NAMES {
+        result = f"{result}{name}";
+    };
+    result
+}
@@ -0,0 +1,38 @@
+import utils;
+from utils import add, SCALE;
+from shapes import Point, Shape, Line, Dot;
+
+def qualified_add(a: int, b: int) -> int {
+    utils.add(a, b)
+}
+
+def imported_add(a: int, b: int) -> int {
+    add(a, b)
+}
+
+def scaled(x: int) -> int {
+    utils.scale(x) + SCALE
+}
+
+def calls() -> int {
+    utils.calls
+}
+
+def moved_start(by: int) -> int {
+    let p: Point = utils.moved(utils.start(), by);
+    p.x + p.y
+}
+
+def line_length(by: int) -> int {
+    let line: Shape = Line(Point(0, 0), utils.moved(Point(1, 1), by));
+
+    match line {
+        Line(start, end) => end.x - start.x,
+        Dot(_) => 0,
+    }
+}
+
+def apply(x: int) -> int {
+    let f: (int) -> int = utils.scale;
+    f(x)
+}
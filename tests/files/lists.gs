@@ -0,0 +1,52 @@
+
+def simple_list() -> [int] {
+    [1, 2, 3]
+}
+
+def expr_list(a: int) -> [int] {
+    let b: int = 4;
+    [a, a + b, a * b]
+}
+
+def mixed_list() -> [int | bool] {
+    let g: [int | bool] = [1, 2 + 3, true];
+    g
+}
+
+def nested_list() -> [[int] | str] {
+    [[1, 2], [], "three"]
+}
+
+def empty_list() -> [str] {
+    []
+}
+
+def pass_list(a: [int]) -> [int] {
+    a
+}
+
+def compare_lists(a: [int], b: [int]) -> bool {
+    a == b
+}
+
+def index_list(a: [int], i: int) -> int {
+    a[i]
+}
+
+def index_str(s: str, i: int) -> str {
+    s[i]
+}
+
+def slice_list(a: [int], start: int, end: int) -> [int] {
+    a[start:end]
+}
+
+def slice_str(s: str) -> [str] {
+    [s[1:3], s[:2], s[2:], s[:], s[-3:-1]]
+}
+
+def nested_index() -> int {
+    let a: [[int] | int] = [[1, 2], 3];
+    let b: [[int]] = [[1, 2], [3, 4, 5]];
+    b[1][-1] + [10, 20][0]
+}
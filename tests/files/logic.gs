@@ -0,0 +1,40 @@
+
+def logic_and(a: bool, b: bool) -> bool {
+    a and b
+}
+
+def logic_or(a: bool, b: bool) -> bool {
+    a or b
+}
+
+def logic_not(a: bool) -> bool {
+    not a
+}
+
+def guard(a: int, b: int) -> bool {
+    a > 0 and b < 10 or not a != 5
+}
+
+def truthy(a: int, b: str) -> bool {
+    a and b
+}
+
+def boom(s: str) -> bool {
+    s < 1
+}
+
+def and_short_circuit() -> bool {
+    false and boom("x")
+}
+
+def or_short_circuit() -> bool {
+    true or boom("x")
+}
+
+def while_and() -> int {
+    let a: int = 0;
+    while a < 10 and not a == 4 {
+        a = a + 1;
+    };
+    a
+}
@@ -0,0 +1,71 @@
+def simple_map() -> {str: int} {
+    ["one": 1, "two": 2, "three": 3]
+}
+
+def empty_map() -> {int: str} {
+    [:]
+}
+
+def mixed_map() -> {int | bool: [int] | str} {
+    [1: [1, 2], true: "yes",]
+}
+
+def lookup(m: {str: int}, key: str) -> int {
+    m[key]
+}
+
+def nested_lookup() -> int {
+    let m: {str: {str: int}} = ["a": ["b": 4]];
+    m["a"]["b"] * 2
+}
+
+def insert(m: {str: int}, key: str, value: int) -> {str: int} {
+    m[key] = value;
+    m
+}
+
+def copy_on_write(m: {str: int}) -> bool {
+    let original: {str: int} = m;
+    m["new"] = 10;
+    original == m
+}
+
+def update_list(a: [int]) -> [int] {
+    a[-1] = 10;
+    a[0] = a[0] * 2;
+    a
+}
+
+def update_nested() -> int {
+    let m: {str: [int]} = ["a": [1, 2], "b": [3]];
+    m["a"][1] = 5;
+    m["a"][0] + m["a"][1] + m["b"][0]
+}
+
+def has_key(m: {str: int}, key: str) -> bool {
+    key in m
+}
+
+def missing_key(m: {str: int}, key: str) -> bool {
+    key not in m
+}
+
+def membership() -> [bool] {
+    [2 in [1, 2, 3], 5 in [1, 2, 3], "ar" in "gears", "x" not in "gears"]
+}
+
+def sum_values(m: {str: int}) -> int {
+    let total: int = 0;
+
+    for key in m {
+        total = total + m[key];
+    };
+    total
+}
+
+def first_key(m: {str: int}) -> str | none {
+    for key in m {
+        return key;
+    };
+    return;
+}
@@ -2,3 +2,67 @@
 def basic_math() -> int {
     4 + 3 * 5 - 42 / 6
 }
+
+def float_math() -> float {
+    1.5 * 4 + 2.5e1 / 10
+}
+
+def mixed_math(a: int, b: float) -> float {
+    a + b
+}
+
+def float_compare(a: float, b: int) -> bool {
+    a < b
+}
+
+def negate(a: int) -> int {
+    -a
+}
+
+def unary_math(a: int) -> int {
+    -a * 2 - -3 + +a
+}
+
+def negative_float() -> float {
+    -2.5 * -2
+}
+
+def min_int() -> int {
+    -9223372036854775808
+}
+
+def modulo(a: int, b: int) -> int {
+    a % b
+}
+
+def floor_div(a: int, b: int) -> int {
+    a // b
+}
+
+def power(a: int, b: int) -> int {
+    a ** b
+}
+
+def precedence() -> int {
+    -2 ** 2 + 2 ** 3 ** 2 % 7 * 3 // 2
+}
+
+def float_ops(a: float, b: float) -> float {
+    a % b + a // b + a ** 2
+}
+
+def divide(a: int, b: int) -> int {
+    a / b
+}
+
+def add(a: int, b: int) -> int {
+    a + b
+}
+
+def subtract(a: int, b: int) -> int {
+    a - b
+}
+
+def multiply(a: int, b: int) -> int {
+    a * b
+}
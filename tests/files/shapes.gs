@@ -0,0 +1,13 @@
+struct Point {
+    x: int,
+    y: int,
+}
+
+enum Shape {
+    Dot(Point),
+    Line(Point, Point),
+}
+
+def origin() -> Point {
+    Point(0, 0)
+}
@@ -2,3 +2,23 @@
 def simple_string() -> str {
     "simple string"
 }
+
+def escapes() -> str {
+    "tab:\t quote:\" slash:\\ \u{263A}\nend"
+}
+
+def empty_string() -> str {
+    ""
+}
+
+def total(a: int, b: int) -> str {
+    f"total: {a + b}"
+}
+
+def describe(name: str, scores: [int], ratio: float, done: bool) -> str {
+    f"{name} has {scores} at {ratio}, done: {done} {{{none}}}"
+}
+
+def nested_format(items: {str: (int, str)}) -> str {
+    f"{items} {f'{"a}"}'}"
+}
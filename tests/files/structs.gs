@@ -0,0 +1,64 @@
+struct Point {
+    x: int,
+    y: int,
+}
+
+struct Line {
+    start: Point,
+    end: Point,
+    label: str | none,
+}
+
+def new_point(x: int, y: int) -> Point {
+    Point(x, y)
+}
+
+def get_x(p: Point) -> int {
+    p.x
+}
+
+def length_squared(l: Line) -> int {
+    let dx: int = l.end.x - l.start.x;
+    let dy: int = l.end.y - l.start.y;
+    dx * dx + dy * dy
+}
+
+def make_line() -> Line {
+    Line(Point(0, 0), new_point(3, 4), none_label())
+}
+
+def none_label() -> str | none {
+    return;
+}
+
+def move_point(p: Point, dx: int) -> Point {
+    p.x = p.x + dx;
+    p
+}
+
+def copy_on_write(p: Point) -> bool {
+    let original: Point = p;
+    p.y = 100;
+    original == p
+}
+
+def points_equal(a: Point, b: Point) -> bool {
+    a == b
+}
+
+def move_end(l: Line, dy: int) -> int {
+    l.end.y = l.end.y + dy;
+    l.end.y
+}
+
+def relabel(label: str) -> [str | none] {
+    let lines: [Line] = [make_line(), make_line()];
+    lines[1].label = label;
+    [lines[0].label, lines[1].label]
+}
+
+def nested_copy_on_write(l: Line) -> bool {
+    let original: Line = l;
+    l.start.x = 7;
+    original.start == l.start
+}
@@ -0,0 +1,50 @@
+def pair(a: int, b: str) -> (int, str) {
+    (a, b)
+}
+
+def single() -> (int,) {
+    (1,)
+}
+
+def nested() -> ((int, bool), [int]) {
+    ((1, true), [2, 3])
+}
+
+def divmod(a: int, b: int) -> (int, int) {
+    (a // b, a % b)
+}
+
+def destructure(a: int, b: int) -> int {
+    let (q, r): (int, int) = divmod(a, b);
+    q * 100 + r
+}
+
+def destructure_nested() -> int {
+    let ((a, b), c): ((int, int), int) = ((1, 2), 3);
+    a * 100 + b * 10 + c
+}
+
+def swap(a: int, b: int) -> (int, int) {
+    (a, b) = (b, a);
+    (a, b)
+}
+
+def index_tuple(t: (int, str)) -> int {
+    t[0] + 1
+}
+
+def sum_pairs(pairs: [(int, int)]) -> int {
+    let total: int = 0;
+
+    for (a, b) in pairs {
+        total = total + a * b;
+    };
+    total
+}
+
+def shadow() -> int {
+    let a: int = 1;
+    let a: int = a + 1;
+    let b: int = 10;
+    a + b
+}
@@ -0,0 +1,21 @@
+import shapes;
+
+const SCALE: int = 3;
+let calls: int = 0;
+
+def add(a: int, b: int) -> int {
+    calls = calls + 1;
+    a + b
+}
+
+def scale(x: int) -> int {
+    x * SCALE
+}
+
+def moved(p: shapes.Point, by: int) -> shapes.Point {
+    shapes.Point(p.x + by, p.y + by)
+}
+
+def start() -> shapes.Point {
+    shapes.origin()
+}
@@ -18,6 +18,16 @@ def example(a: int, b: int | bool) -> int | None {
     let d: bool = false;
     let e: bool = true;
     let string: str = "Test";
+    let inferred = a + 1;
+    const limit: int = 10;
+}
+
+def defaults(a: int, b: int = 10) {
+    defaults(1, b: 3);
+}
+
+def generic<T>(items: [T], fallback: T?) -> T? {
+    fallback
 }
 
 def branching() {
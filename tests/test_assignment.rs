@@ -1,7 +1,7 @@
 #[macro_use]
 extern crate gears_lang;
 
-use gears_lang::compiler::compile_file;
+use gears_lang::compiler::{compile_file, compile_str};
 use gears_lang::module::disassemble;
 use gears_lang::object::GearsObject;
 use gears_lang::vm::execute_function;
@@ -38,3 +38,101 @@ fn reassignment() {
         gears_obj!(9)
     );
 }
+
+#[test]
+fn inferred_assignment() {
+    let module = compile_file("tests/files/assignment.gs").expect("Test failure");
+
+    disassemble(&module, "inferred");
+
+    assert_eq!(
+        execute_function(&module, "inferred", vec![gears_obj!(3), gears_obj!(4)]).unwrap(),
+        gears_obj!(14)
+    );
+    assert_eq!(
+        execute_function(&module, "inferred_union", vec![gears_obj!(false)]).unwrap(),
+        gears_obj!("one")
+    );
+    assert_eq!(
+        execute_function(&module, "inferred_function", vec![]).unwrap(),
+        gears_obj!(42)
+    );
+}
+
+#[test]
+fn bad_inference() {
+    let bad = [
+        // The annotation is still checked
+        "def f() { let a: int = \"one\"; }",
+        // The inferred type constrains later uses
+        "def f() -> str { let a = 1; a }",
+        "def f() { let a = 1; a[0] = 2; }",
+        // Empty containers need an annotation
+        "def f() { let a = []; }",
+        "def f() { let a = [:]; }",
+        "def f() { let (a, b) = ([], 1); }",
+        "let items = [];",
+    ];
+
+    for source in bad.iter() {
+        assert!(
+            compile_str(source, "bad_inference").is_err(),
+            "{} should not compile",
+            source
+        );
+    }
+
+    let module = compile_str("const RATE = 5; def f() -> int { RATE }", "inferred_global")
+        .expect("Test failure");
+    assert_eq!(
+        execute_function(&module, "f", vec![]).unwrap(),
+        gears_obj!(5)
+    );
+}
+
+#[test]
+fn const_assignment() {
+    let module = compile_file("tests/files/assignment.gs").expect("Test failure");
+
+    disassemble(&module, "constants");
+
+    assert_eq!(
+        execute_function(&module, "constants", vec![gears_obj!(4)]).unwrap(),
+        gears_obj!(23)
+    );
+}
+
+#[test]
+fn bad_reassignment() {
+    let bad = [
+        // Reassignments keep to the declared types
+        "def f() { let a: int = 1; a = \"text\"; }",
+        "def f() { let a = 1; a = none; }",
+        "def f() { let (a, b) = (1, 2); (a, b) = (3, true); }",
+        "def f(a: int) { a = 1.5; }",
+        "def f() { for i in 0 to 5 { i = \"text\"; } }",
+        "def f() { for x in [1, 2] { x = [x]; } }",
+        "let count = 0; def f() { count = false; }",
+        // A reassigned variable loses its narrowed types
+        "def f(a: int | none) -> int { if a != none { a = 1; a + 1 } else { 0 } }",
+        "def f(a: int?, b: bool) -> int { if a == none { return 0; }; if b { a = none; }; a + 1 }",
+        "def f(a: int?, b: bool) -> int { if a != none { if b { a = none; }; a + 1 } else { 0 } }",
+        "def f(a: int?) -> int { if a == none { return 0; }; while true { a + 1; a = none; }; 0 }",
+        "def f(a: int?) -> int { if a == none { return 0; }; for i in 0 to 2 { a + i; a = none; }; 0 }",
+        "def f(a: int?, b: bool) -> int { if a == none { return 0; }; match b { true => { a = none; }, false => {} }; a + 1 }",
+        "def f(a: int?, b: bool) -> bool { a != none and if b { a = none; true } else { true } and a + 1 > 1 }",
+        "def f(a: int?, b: bool) -> bool { if (a != none and if b { a = none; true } else { true }) { a + 1 > 1 } else { false } }",
+        // Constants can not be reassigned
+        "def f() { const a = 1; a = 2; }",
+        "def f() { const (a, b) = (1, 2); b = 3; }",
+        "def f() { const a = 1; let g = def() { a = 2; }; }",
+    ];
+
+    for source in bad.iter() {
+        assert!(
+            compile_str(source, "bad_reassignment").is_err(),
+            "{} should not compile",
+            source
+        );
+    }
+}
@@ -6,6 +6,7 @@ extern crate cached;
 extern crate lazy_static;
 
 use gears_lang::compiler::compile_file;
+use gears_lang::errors::GearsError;
 use gears_lang::module::{disassemble, Module};
 use gears_lang::object::{GearsObject, FALSE_OBJ, NONE_OBJ, TRUE_OBJ};
 use gears_lang::vm::execute_function;
@@ -58,7 +59,7 @@ fn test_five_or_none_true() {
 #[test]
 fn test_five_or_none_false() {
     assert_eq!(
-        execute_function(&setup(), "five_or_none", vec![NONE_OBJ.clone()]).unwrap(),
+        execute_function(&setup(), "five_or_none", vec![FALSE_OBJ.clone()]).unwrap(),
         NONE_OBJ.clone()
     );
 }
@@ -80,3 +81,164 @@ fn test_simple_for_loop() {
         gears_obj!(19)
     );
 }
+
+#[test]
+fn test_expr_range() {
+    assert_eq!(
+        execute_function(&setup(), "expr_range", vec![gears_obj!(1), gears_obj!(4)]).unwrap(),
+        gears_obj!(2 + 3 + 4)
+    );
+}
+
+#[test]
+fn test_step_range() {
+    assert_eq!(
+        execute_function(&setup(), "step_range", vec![gears_obj!(3)]).unwrap(),
+        gears_obj!(30609)
+    );
+    assert_eq!(
+        execute_function(&setup(), "step_range", vec![gears_obj!(-1)]).unwrap(),
+        gears_obj!(0)
+    );
+
+    match execute_function(&setup(), "step_range", vec![gears_obj!(0)]) {
+        Err(GearsError::ArithmeticError(_)) => {}
+        other => panic!("Zero step returned {:?}", other),
+    }
+}
+
+#[test]
+fn test_range_near_limits() {
+    assert_eq!(
+        execute_function(&setup(), "range_near_limits", vec![]).unwrap(),
+        gears_obj!(505)
+    );
+}
+
+#[test]
+fn test_late_loop() {
+    // The loops start after the first 256 bytes of the function
+    assert_eq!(
+        execute_function(&setup(), "late_loop", vec![]).unwrap(),
+        gears_obj!(1600)
+    );
+}
+
+#[test]
+fn test_descending_range() {
+    assert_eq!(
+        execute_function(&setup(), "descending_range", vec![]).unwrap(),
+        gears_obj!(3020100)
+    );
+}
+
+#[test]
+fn test_iterate_list() {
+    assert_eq!(
+        execute_function(&setup(), "sum_list", vec![gears_obj!(list 4, 5, 6)]).unwrap(),
+        gears_obj!(15)
+    );
+    assert_eq!(
+        execute_function(&setup(), "sum_list", vec![gears_obj!(list)]).unwrap(),
+        gears_obj!(0)
+    );
+    assert_eq!(
+        execute_function(&setup(), "nested_loops", vec![]).unwrap(),
+        gears_obj!(6)
+    );
+}
+
+#[test]
+fn test_iterate_string() {
+    assert_eq!(
+        execute_function(
+            &setup(),
+            "count_letter",
+            vec![gears_obj!("banana"), gears_obj!("a")]
+        ).unwrap(),
+        gears_obj!(3)
+    );
+}
+
+#[test]
+fn test_break() {
+    assert_eq!(
+        execute_function(&setup(), "while_break", vec![]).unwrap(),
+        gears_obj!(7)
+    );
+    assert_eq!(
+        execute_function(
+            &setup(),
+            "nested_break",
+            vec![Arc::new(GearsObject::List(vec![
+                gears_obj!(list 1, 2, -1, 50),
+                gears_obj!(list 3, 200),
+                gears_obj!(list 1000),
+            ]))]
+        ).unwrap(),
+        gears_obj!(206)
+    );
+}
+
+#[test]
+fn test_continue() {
+    assert_eq!(
+        execute_function(&setup(), "for_continue", vec![]).unwrap(),
+        gears_obj!(1 + 3 + 5 + 7 + 9)
+    );
+    assert_eq!(
+        execute_function(&setup(), "while_continue", vec![]).unwrap(),
+        gears_obj!(1 + 2 + 3 + 8 + 9 + 10)
+    );
+}
+
+#[test]
+fn test_narrowing() {
+    disassemble(&setup(), "add_or_zero");
+
+    for (a, expected) in vec![(gears_obj!(4), 9), (NONE_OBJ.clone(), 0)] {
+        assert_eq!(
+            execute_function(&setup(), "add_or_zero", vec![a, gears_obj!(5)]).unwrap(),
+            gears_obj!(expected)
+        );
+    }
+
+    for (a, expected) in vec![(gears_obj!(4), 8), (NONE_OBJ.clone(), 3)] {
+        assert_eq!(
+            execute_function(&setup(), "default_to", vec![a, gears_obj!(3)]).unwrap(),
+            gears_obj!(expected)
+        );
+    }
+
+    for (a, b, expected) in vec![
+        (gears_obj!(4), gears_obj!(7), true),
+        (gears_obj!(4), gears_obj!(1), false),
+        (NONE_OBJ.clone(), gears_obj!(20), false),
+    ] {
+        assert_eq!(
+            execute_function(&setup(), "both_set", vec![a, b]).unwrap(),
+            gears_obj!(expected)
+        );
+    }
+
+    for (a, expected) in vec![(NONE_OBJ.clone(), -1), (gears_obj!(0), 0), (gears_obj!(5), 6)] {
+        assert_eq!(
+            execute_function(&setup(), "match_optional", vec![a]).unwrap(),
+            gears_obj!(expected)
+        );
+    }
+    // Reassignments are checked against the declared types, not the narrowed ones
+    for a in vec![gears_obj!(4), NONE_OBJ.clone()] {
+        assert_eq!(
+            execute_function(&setup(), "clear_if_set", vec![a]).unwrap(),
+            NONE_OBJ.clone()
+        );
+    }
+
+    for (a, expected) in vec![(gears_obj!(4), 8), (NONE_OBJ.clone(), 0)] {
+        assert_eq!(
+            execute_function(&setup(), "clear_after_guard", vec![a]).unwrap(),
+            gears_obj!(expected)
+        );
+    }
+}
@@ -0,0 +1,88 @@
+#[macro_use]
+extern crate gears_lang;
+#[macro_use]
+extern crate cached;
+#[macro_use]
+extern crate lazy_static;
+
+use gears_lang::compiler::{compile_file, compile_str};
+use gears_lang::errors::GearsError;
+use gears_lang::module::{disassemble, Module};
+use gears_lang::object::GearsObject;
+use gears_lang::vm::execute_function;
+use std::sync::Arc;
+
+cached!{
+    FIB;
+    fn setup() -> Module = {
+        compile_file("tests/files/closures.gs").expect("Test failure")
+    }
+}
+
+#[test]
+fn test_function_values() {
+    disassemble(&setup(), "apply_named");
+    assert_eq!(
+        execute_function(&setup(), "apply_named", vec![gears_obj!(4)]).unwrap(),
+        gears_obj!(8)
+    );
+    assert_eq!(
+        execute_function(&setup(), "function_list", vec![gears_obj!(4)]).unwrap(),
+        gears_obj!(8 + 7)
+    );
+}
+
+#[test]
+fn test_closures() {
+    disassemble(&setup(), "make_adder");
+    let adder = execute_function(&setup(), "make_adder", vec![gears_obj!(5)]).unwrap();
+    assert_eq!(adder.get_type_str(), "Function");
+
+    assert_eq!(
+        execute_function(&setup(), "use_adder", vec![gears_obj!(10)]).unwrap(),
+        gears_obj!(15)
+    );
+    assert_eq!(
+        execute_function(&setup(), "call_result", vec![gears_obj!(1), gears_obj!(2)]).unwrap(),
+        gears_obj!(3)
+    );
+    assert_eq!(
+        execute_function(
+            &setup(),
+            "count_above",
+            vec![gears_obj!(list 1, 5, 10, 20), gears_obj!(4)]
+        ).unwrap(),
+        gears_obj!(3)
+    );
+    assert_eq!(
+        execute_function(&setup(), "captured_by_value", vec![]).unwrap(),
+        gears_obj!(6)
+    );
+    assert_eq!(
+        execute_function(&setup(), "nested_capture", vec![gears_obj!(2), gears_obj!(1)]).unwrap(),
+        gears_obj!(21)
+    );
+}
+
+#[test]
+fn test_bad_functions() {
+    for string in vec![
+        "def test(a: int) -> int { a(1) }",
+        "def test(f: (int) -> int) -> int { f() }",
+        "def test(f: (int) -> int) -> int { f(\"a\") }",
+        "def test(f: (int) -> int) -> str { f(1) }",
+        "def test() -> (int) -> int { def(x: int) -> str { \"a\" } }",
+        "def test() -> (int) -> int { def(x: str) -> int { 1 } }",
+        "def test(f: (int) -> int | none) -> (int) -> int { f }",
+        "def test(f: (int) -> int) -> (int | none) -> int { f }",
+        "def test() -> int { let x: int = 1; let f: () -> int = def() -> int { y }; f() }",
+        // Captured values are copies, so they can not be assigned to
+        "def test() -> int { let x = 1; let f = def() { x = 2; }; f(); x }",
+        "def test() -> int { let x = [1]; let f = def() { x[0] = 2; }; f(); x[0] }",
+    ] {
+        match compile_str(string, "test") {
+            Err(GearsError::TypeError(_)) | Err(GearsError::SymbolNotFound(_)) => {}
+            other => panic!("{} returned {:?}", string, other.map(|_| ())),
+        }
+    }
+}
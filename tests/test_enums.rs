@@ -0,0 +1,153 @@
+#[macro_use]
+extern crate gears_lang;
+#[macro_use]
+extern crate cached;
+#[macro_use]
+extern crate lazy_static;
+
+use gears_lang::compiler::{compile_file, compile_str};
+use gears_lang::errors::GearsError;
+use gears_lang::module::{disassemble, Module};
+use gears_lang::object::{GearsObject, FALSE_OBJ, NONE_OBJ, TRUE_OBJ};
+use gears_lang::vm::execute_function;
+use std::sync::Arc;
+
+cached!{
+    FIB;
+    fn setup() -> Module = {
+        compile_file("tests/files/enums.gs").expect("Test failure")
+    }
+}
+
+#[test]
+fn test_constructors() {
+    disassemble(&setup(), "approve");
+    assert_eq!(
+        execute_function(&setup(), "approve", vec![gears_obj!(5)]).unwrap(),
+        gears_obj!(enum "Status", "Approved", 5)
+    );
+    assert_eq!(
+        execute_function(&setup(), "pending", vec![]).unwrap(),
+        gears_obj!(enum "Status", "Pending")
+    );
+    assert_eq!(
+        setup().get_enum("Shape").unwrap().get_variants(),
+        &vec![("Circle".to_string(), 1), ("Rect".to_string(), 2)]
+    );
+}
+
+#[test]
+fn test_match_variants() {
+    disassemble(&setup(), "describe");
+    let rejected = gears_obj!(enum "Status", "Rejected", "too late");
+
+    assert_eq!(
+        execute_function(&setup(), "describe", vec![gears_obj!(enum "Status", "Pending")])
+            .unwrap(),
+        gears_obj!("pending")
+    );
+    assert_eq!(
+        execute_function(&setup(), "describe", vec![rejected.clone()]).unwrap(),
+        gears_obj!("too late")
+    );
+    assert_eq!(
+        execute_function(
+            &setup(),
+            "area",
+            vec![gears_obj!(enum "Shape", "Rect", 3, 4)]
+        ).unwrap(),
+        gears_obj!(12)
+    );
+    assert_eq!(
+        execute_function(
+            &setup(),
+            "is_square",
+            vec![gears_obj!(enum "Shape", "Rect", 3, 3)]
+        ).unwrap(),
+        TRUE_OBJ.clone()
+    );
+    assert_eq!(
+        execute_function(&setup(), "nested", vec![NONE_OBJ.clone()]).unwrap(),
+        gears_obj!(0)
+    );
+
+    // The none payload is matched first, so the later binding is an int
+    for (value, result) in vec![(GearsObject::None, 7), (GearsObject::Int(3), 3)] {
+        assert_eq!(
+            execute_function(
+                &setup(),
+                "unwrap_or",
+                vec![gears_obj!(enum "Wrap", "Value", value), gears_obj!(7)]
+            ).unwrap(),
+            gears_obj!(result)
+        );
+    }
+}
+
+#[test]
+fn test_match_literals() {
+    for (status, expected) in vec![
+        (gears_obj!(enum "Status", "Approved", 0), -1),
+        (gears_obj!(enum "Status", "Approved", 4), 8),
+        (gears_obj!(enum "Status", "Pending"), 0),
+    ] {
+        assert_eq!(
+            execute_function(&setup(), "amount", vec![status]).unwrap(),
+            gears_obj!(expected)
+        );
+    }
+
+    for (n, expected) in vec![(1, "one"), (2, "two"), (-1, "minus one"), (7, "many")] {
+        assert_eq!(
+            execute_function(&setup(), "name_of", vec![gears_obj!(n)]).unwrap(),
+            gears_obj!(expected)
+        );
+    }
+
+    assert_eq!(
+        execute_function(&setup(), "flip", vec![TRUE_OBJ.clone()]).unwrap(),
+        FALSE_OBJ.clone()
+    );
+}
+
+#[test]
+fn test_big_match() {
+    // The jumps between the arms do not fit in a single byte
+    for (n, expected) in vec![(0, 0), (1, 2), (5, 30), (10, 110), (11, 0), (-3, 0)] {
+        assert_eq!(
+            execute_function(&setup(), "big_match", vec![gears_obj!(n)]).unwrap(),
+            gears_obj!(expected)
+        );
+    }
+}
+
+#[test]
+fn test_bad_enums() {
+    let enums = "enum Status { Pending, Approved(int), Rejected(str) }\n";
+
+    for string in vec![
+        "def test(s: Status) -> int { match s { Pending => 0, Approved(n) => n } }",
+        "def test(s: Status) -> int { match s { Approved(1) => 0, Pending => 0, Rejected(_) => 0 } }",
+        "def test(n: int) -> int { match n { 1 => 0 } }",
+        "def test(b: bool) -> int { match b { true => 0 } }",
+        "def test(s: Status) -> int { match s { Approved => 0, _ => 1 } }",
+        "def test(s: Status) -> int { match s { Approved(\"a\") => 0, _ => 1 } }",
+        "def test(s: int) -> int { match s { Pending => 0, _ => 1 } }",
+        "def test(s: int) -> int { match s { \"a\" => 0, _ => 1 } }",
+        "def test(s: Status) -> int { match s { Approved(n) => n, Rejected(r) => r, _ => 0 } }",
+        "enum Wrap { Value(int?) }\ndef test(w: Wrap) -> int { match w { Value(1) => 0, Value(n) => n } }",
+        "enum Pair { Both(bool, int?) }\ndef test(p: Pair) -> int { match p { Both(_, none) => 0, Both(b, none) => 1, _ => 2 } }",
+        "def test() -> Status { Approved(\"a\") }",
+        "def test() -> Status { Approved }",
+        "def test() -> Status { Pending(1) }",
+        "def test() -> int { Pending }",
+        "enum Twice { A, A }",
+        "enum Other { Pending }",
+        "enum str { A }",
+    ] {
+        match compile_str(&format!("{}{}", enums, string), "test") {
+            Err(GearsError::TypeError(_)) => {}
+            other => panic!("{} returned {:?}", string, other.map(|_| ())),
+        }
+    }
+}
@@ -5,11 +5,12 @@ extern crate cached;
 #[macro_use]
 extern crate lazy_static;
 
-use gears_lang::compiler::compile_file;
+use gears_lang::compiler::{compile_file, compile_str};
 use gears_lang::errors::*;
 use gears_lang::module::Module;
-use gears_lang::object::{GearsObject, NONE_OBJ};
-use gears_lang::vm::execute_function;
+use gears_lang::object::{ArcGearsObject, GearsObject, NONE_OBJ};
+use gears_lang::types::{Type, Types};
+use gears_lang::vm::{execute_function, execute_function_named};
 use std::sync::Arc;
 
 cached!{
@@ -82,3 +83,362 @@ fn pass_too_many_args() {
         },
     }
 }
+
+#[test]
+fn early_return() {
+    assert_eq!(
+        execute_function(&setup(), "early_return", vec![gears_obj!(-4)]).unwrap(),
+        gears_obj!(0)
+    );
+    assert_eq!(
+        execute_function(&setup(), "early_return", vec![gears_obj!(4)]).unwrap(),
+        gears_obj!(8)
+    );
+}
+
+#[test]
+fn return_in_loop() {
+    assert_eq!(
+        execute_function(
+            &setup(),
+            "return_in_loop",
+            vec![gears_obj!(list 5, 6, 7), gears_obj!(6)]
+        ).unwrap(),
+        gears_obj!(1)
+    );
+    assert_eq!(
+        execute_function(
+            &setup(),
+            "return_in_loop",
+            vec![gears_obj!(list 5, 6, 7), gears_obj!(9)]
+        ).unwrap(),
+        NONE_OBJ.clone()
+    );
+}
+
+#[test]
+fn return_from_branches() {
+    assert_eq!(
+        execute_function(&setup(), "branches_return", vec![gears_obj!(true)]).unwrap(),
+        gears_obj!("yes")
+    );
+    assert_eq!(
+        execute_function(&setup(), "branches_return", vec![gears_obj!(false)]).unwrap(),
+        gears_obj!("no")
+    );
+    assert_eq!(
+        execute_function(&setup(), "nested_return", vec![gears_obj!(3)]).unwrap(),
+        gears_obj!(30)
+    );
+    assert_eq!(
+        execute_function(&setup(), "nested_return", vec![gears_obj!(30)]).unwrap(),
+        gears_obj!(-1)
+    );
+}
+
+#[test]
+fn default_args() {
+    assert_eq!(
+        execute_function(&setup(), "scale_defaults", vec![gears_obj!(4)]).unwrap(),
+        gears_obj!(40)
+    );
+    assert_eq!(
+        execute_function(&setup(), "greet", vec![]).unwrap(),
+        gears_obj!("hello world!")
+    );
+    assert_eq!(
+        execute_function(&setup(), "with_default", vec![]).unwrap(),
+        gears_obj!(3)
+    );
+
+    // The host can leave off args that have defaults
+    assert_eq!(
+        execute_function(&setup(), "scale", vec![gears_obj!(4), gears_obj!(2)]).unwrap(),
+        gears_obj!(8)
+    );
+}
+
+#[test]
+fn keyword_args() {
+    assert_eq!(
+        execute_function(&setup(), "scale_keywords", vec![gears_obj!(4)]).unwrap(),
+        gears_obj!(43 + 8)
+    );
+    assert_eq!(
+        execute_function(&setup(), "greet_keyword", vec![]).unwrap(),
+        gears_obj!("hello world?")
+    );
+}
+
+#[test]
+fn keyword_arg_order() {
+    // Args are computed in the order they are written, defaults after them
+    assert_eq!(
+        execute_function(&setup(), "keyword_order", vec![]).unwrap(),
+        gears_obj!(312005)
+    );
+    assert_eq!(
+        execute_function(&setup(), "keyword_order_defaults", vec![]).unwrap(),
+        gears_obj!(31013)
+    );
+    assert_eq!(
+        execute_function(&setup(), "field_order", vec![]).unwrap(),
+        gears_obj!(2112)
+    );
+}
+
+#[test]
+fn host_keyword_args() {
+    assert_eq!(
+        execute_function_named(
+            &setup(),
+            "scale",
+            vec![("offset", gears_obj!(1)), ("a", gears_obj!(2))]
+        ).unwrap(),
+        gears_obj!(21)
+    );
+    assert_eq!(
+        execute_function_named(&setup(), "greet", vec![("name", gears_obj!("gears"))]).unwrap(),
+        gears_obj!("hello gears!")
+    );
+
+    let errors = vec![
+        (vec![("offset", gears_obj!(1))], "missing"),
+        (vec![("b", gears_obj!(1))], "unknown"),
+        (vec![("a", gears_obj!(1)), ("a", gears_obj!(2))], "duplicate"),
+    ];
+
+    for (args, kind) in errors {
+        match (execute_function_named(&setup(), "scale", args), kind) {
+            (Err(GearsError::InterOpError { error, .. }), kind) => match (error, kind) {
+                (InterOpErrorType::TooFewArgs, "missing")
+                | (InterOpErrorType::UnknownArg, "unknown")
+                | (InterOpErrorType::DuplicateArg, "duplicate") => {}
+                (error, kind) => panic!("Wrong error returned for {}: {:?}", kind, error),
+            },
+            (result, kind) => panic!("Wrong result for {}: {:?}", kind, result),
+        }
+    }
+}
+
+#[test]
+fn bad_call_args() {
+    let bad = [
+        "def f(a: int, b: int = 1) -> int { a + b } def g() -> int { f() }",
+        "def f(a: int) -> int { a } def g() -> int { f(1, 2) }",
+        "def f(a: int) -> int { a } def g() -> int { f(b: 1) }",
+        "def f(a: int) -> int { a } def g() -> int { f(1, a: 1) }",
+        "def f(a: int) -> int { a } def g() -> int { f(a: 1, 2) }",
+        "def f(a: int = \"one\") -> int { a }",
+        "def f(a: int = b) -> int { a }",
+        "def f(a: int, b: int = a) -> int { a }",
+        "struct Point { x: int = 0 }",
+        "def f() { let g: (int) -> int = def(a: int = 1) -> int { a }; }",
+        "def f(a: int) -> int { a } def g() -> int { let h: (int) -> int = f; h(a: 1) }",
+    ];
+
+    for source in bad.iter() {
+        assert!(
+            compile_str(source, "bad_call_args").is_err(),
+            "{} should not compile",
+            source
+        );
+    }
+
+    let source = "def f(a: int) -> int { a }\ndef g() -> int { f(a: 1, 2) }";
+    match compile_str(source, "bad_call_args") {
+        Err(GearsError::ParseError { location, message }) => {
+            assert_eq!((location.line(), location.column()), (2, 19));
+            assert_eq!(
+                message,
+                "Args given by position have to come before args given by name at Line: 2, Char: 19"
+            );
+        }
+        other => panic!("Expected a parse error, found: {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn wrong_arg_type() {
+    let calls = vec![
+        ("add", vec![gears_obj!("x"), gears_obj!(1)], "a"),
+        ("add", vec![gears_obj!(1), gears_obj!(1.5)], "b"),
+        (
+            "return_in_loop",
+            vec![gears_obj!(1), gears_obj!(1)],
+            "items",
+        ),
+    ];
+
+    for (function, args, param) in calls {
+        match execute_function(&setup(), function, args) {
+            Err(GearsError::InterOpError {
+                error: InterOpErrorType::WrongArgType,
+                message,
+            }) => assert!(
+                message.contains(&format!("arg: {},", param)),
+                "{} does not name {}",
+                message,
+                param
+            ),
+            result => panic!("Wrong result for {}: {:?}", function, result),
+        }
+    }
+
+    match execute_function_named(&setup(), "scale", vec![("a", gears_obj!(true))]) {
+        Err(GearsError::InterOpError {
+            error: InterOpErrorType::WrongArgType,
+            ..
+        }) => {}
+        result => panic!("Wrong result for scale: {:?}", result),
+    }
+}
+
+#[test]
+fn function_signature() {
+    let module = setup();
+    let function = module.get_function("return_in_loop").unwrap();
+    let params: Vec<(&str, &Types)> = function
+        .get_params()
+        .iter()
+        .map(|param| (param.get_name(), param.get_types()))
+        .collect();
+
+    assert_eq!(
+        params,
+        vec![
+            ("items", &vec![Type::new_list(vec![Type::Int])]),
+            ("target", &vec![Type::Int]),
+        ]
+    );
+    assert_eq!(function.get_return_types(), &vec![Type::None, Type::Int]);
+}
+
+#[test]
+fn many_functions() {
+    // Indexes past 255 do not fit in one byte of the opcodes
+    let mut source = String::new();
+    for index in 0..300 {
+        source.push_str(&format!("def f{}() -> int {{ {} }}\n", index, index * 1000));
+    }
+    source.push_str("def call_last() -> int { f299() }\n");
+    source.push_str("def pass_last() -> int { let f = f299; f() }\n");
+
+    let module = compile_str(&source, "many_functions").unwrap();
+
+    assert_eq!(
+        execute_function(&module, "call_last", vec![]).unwrap(),
+        gears_obj!(299000)
+    );
+    assert_eq!(
+        execute_function(&module, "pass_last", vec![]).unwrap(),
+        gears_obj!(299000)
+    );
+}
+
+#[test]
+fn wrong_arg_shape() {
+    let module = compile_str(
+        r#"
+        struct Point { x: int, y: int }
+        struct Line { start: Point, end: Point }
+        enum Shape { Dot(Point), Circle(Point, int), Empty }
+
+        def line_x(line: Line) -> int { line.start.x + line.end.x }
+        def size(shape: Shape) -> int {
+            match shape { Dot(_) => 0, Circle(_, r) => r, Empty => -1 }
+        }
+        def apply(f: (int) -> int, x: int) -> int { f(x) }
+        def double(x: int) -> int { x * 2 }
+        def add(a: int, b: int) -> int { a + b }
+        def get_double() -> (int) -> int { double }
+        def get_add() -> (int, int) -> int { add }
+        "#,
+        "shapes",
+    ).unwrap();
+
+    let point = |x: GearsObject, y: GearsObject| {
+        Arc::new(GearsObject::Struct {
+            name: "Point".to_string(),
+            fields: vec![("x".to_string(), Arc::new(x)), ("y".to_string(), Arc::new(y))],
+        })
+    };
+    let line = |fields: Vec<(&str, ArcGearsObject)>| {
+        Arc::new(GearsObject::Struct {
+            name: "Line".to_string(),
+            fields: fields.into_iter().map(|(f, v)| (f.to_string(), v)).collect(),
+        })
+    };
+    let shape = |variant: &str, values: Vec<ArcGearsObject>| {
+        Arc::new(GearsObject::Enum {
+            name: "Shape".to_string(),
+            variant: variant.to_string(),
+            values,
+        })
+    };
+    let origin = point(GearsObject::Int(0), GearsObject::Int(0));
+    let double = execute_function(&module, "get_double", vec![]).unwrap();
+    let add = execute_function(&module, "get_add", vec![]).unwrap();
+
+    assert_eq!(
+        execute_function(
+            &module,
+            "line_x",
+            vec![line(vec![
+                ("start", origin.clone()),
+                ("end", point(GearsObject::Int(3), GearsObject::Int(4))),
+            ])]
+        ).unwrap(),
+        gears_obj!(3)
+    );
+    assert_eq!(
+        execute_function(&module, "size", vec![shape("Circle", vec![origin.clone(), gears_obj!(5)])])
+            .unwrap(),
+        gears_obj!(5)
+    );
+    assert_eq!(
+        execute_function(&module, "apply", vec![double, gears_obj!(4)]).unwrap(),
+        gears_obj!(8)
+    );
+
+    // The fields and payloads are checked against the declarations
+    let calls = vec![
+        (
+            "line_x",
+            vec![line(vec![
+                ("start", point(GearsObject::Str("a".to_string()), GearsObject::Int(0))),
+                ("end", origin.clone()),
+            ])],
+        ),
+        ("line_x", vec![line(vec![("start", origin.clone())])]),
+        (
+            "line_x",
+            vec![line(vec![("start", origin.clone()), ("stop", origin.clone())])],
+        ),
+        ("line_x", vec![line(vec![("start", origin.clone()), ("end", gears_obj!(1))])]),
+        ("size", vec![shape("Circle", vec![origin.clone()])]),
+        ("size", vec![shape("Circle", vec![origin.clone(), gears_obj!("big")])]),
+        ("size", vec![shape("Square", vec![])]),
+        (
+            "apply",
+            vec![
+                Arc::new(GearsObject::Function {
+                    index: 9999,
+                    captures: vec![],
+                }),
+                gears_obj!(1),
+            ],
+        ),
+        ("apply", vec![add, gears_obj!(1)]),
+    ];
+
+    for (function, args) in calls {
+        match execute_function(&module, function, args.clone()) {
+            Err(GearsError::InterOpError {
+                error: InterOpErrorType::WrongArgType,
+                ..
+            }) => {}
+            result => panic!("Wrong result for {} {:?}: {:?}", function, args, result),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+#[macro_use]
+extern crate gears_lang;
+#[macro_use]
+extern crate cached;
+#[macro_use]
+extern crate lazy_static;
+
+use gears_lang::compiler::{compile_file, compile_str};
+use gears_lang::errors::GearsError;
+use gears_lang::module::{disassemble, Module};
+use gears_lang::object::GearsObject;
+use gears_lang::vm::execute_function;
+use std::sync::Arc;
+
+cached!{
+    FIB;
+    fn setup() -> Module = {
+        compile_file("tests/files/generics.gs").expect("Test failure")
+    }
+}
+
+#[test]
+fn test_generic_calls() {
+    disassemble(&setup(), "first");
+    disassemble(&setup(), "use_first");
+
+    assert_eq!(
+        execute_function(&setup(), "use_first", vec![]).unwrap(),
+        gears_obj!(7)
+    );
+    assert_eq!(
+        execute_function(&setup(), "use_swap", vec![]).unwrap(),
+        gears_obj!(tuple "one", 1)
+    );
+    assert_eq!(
+        execute_function(&setup(), "use_apply_twice", vec![]).unwrap(),
+        gears_obj!(20)
+    );
+    assert_eq!(
+        execute_function(&setup(), "use_nested_first", vec![]).unwrap(),
+        gears_obj!("a")
+    );
+
+    for (flag, expected) in vec![(true, 104), (false, 110)] {
+        assert_eq!(
+            execute_function(&setup(), "use_or_default", vec![gears_obj!(flag)]).unwrap(),
+            gears_obj!(expected)
+        );
+    }
+}
+
+#[test]
+fn test_generics_are_erased() {
+    // The host can call a generic function with any value
+    assert_eq!(
+        execute_function(&setup(), "first", vec![gears_obj!(list "x", "y")]).unwrap(),
+        gears_obj!("x")
+    );
+}
+
+#[test]
+fn test_bad_generics() {
+    let generics = "def first<T>(xs: [T]) -> T { xs[0] }\n";
+
+    for string in vec![
+        // The inferred types are used for the result
+        "def test() -> int { first([\"a\"]) }",
+        "def test() -> int { first(1) }",
+        // A type param is only known to be itself in the body
+        "def add<T>(a: T, b: T) -> T { a + b }",
+        "def one<T>(x: T) -> T { 1 }",
+        "def wrap<T>(x: T) -> [int] { [x] }",
+        // Type params must be new type names
+        "def twice<T, T>(x: T) -> T { x }",
+        "def bad<int>(x: int) -> int { x }",
+        "struct Point { x: int }\ndef bad<Point>(x: Point) -> Point { x }",
+        // A type param only exists in its function
+        "def other(x: T) {}",
+        // Generic functions can only be called
+        "def test() { let f = first; }",
+    ] {
+        assert!(
+            compile_str(&format!("{}{}", generics, string), "bad_generics").is_err(),
+            "{} should not compile",
+            string
+        );
+    }
+}
+
+#[test]
+fn test_generic_errors_name_the_substitution() {
+    let strings = vec![
+        (
+            "def pick<T>(a: T, b: T) -> T { a } def test() -> int { pick(1, \"a\") }",
+            "pick expects the arg b to be [Int], found: [Str], with T = [Int]",
+        ),
+        (
+            "def apply<T>(f: (T) -> T, x: T) -> T { f(x) } def double(x: int) -> int { x * 2 }
+             def test() -> int { apply(double, \"a\") }",
+            "apply expects the arg x to be [Int], found: [Str], with T = [Int]",
+        ),
+        (
+            "def pair<A, B>(a: A, b: [B]) -> (A, B) { (a, b[0]) } def test() { pair(1, 2); }",
+            "pair expects the arg b to be [List([Param(\"B\")])], found: [Int], with A = [Int], B = unknown",
+        ),
+        (
+            "def make<T>() -> [T] { [] } def test() { make(); }",
+            "The type of T can not be inferred from the args of make, with T = unknown",
+        ),
+    ];
+
+    for (string, message) in strings {
+        assert_eq!(
+            compile_str(string, "generic_errors").unwrap_err(),
+            GearsError::TypeError(message.to_string())
+        );
+    }
+}
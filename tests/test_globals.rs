@@ -0,0 +1,161 @@
+#[macro_use]
+extern crate gears_lang;
+#[macro_use]
+extern crate cached;
+#[macro_use]
+extern crate lazy_static;
+
+use gears_lang::compiler::{compile_file, compile_str};
+use gears_lang::errors::{GearsError, InterOpErrorType};
+use gears_lang::module::{disassemble, Module};
+use gears_lang::object::GearsObject;
+use gears_lang::vm::{execute_function, initialize_module};
+use std::sync::Arc;
+
+cached!{
+    FIB;
+    fn setup() -> Module = {
+        compile_file("tests/files/globals.gs").expect("Test failure")
+    }
+}
+
+#[test]
+fn test_read_globals() {
+    disassemble(&setup(), "@init");
+    disassemble(&setup(), "apply_rate");
+    assert_eq!(
+        execute_function(&setup(), "apply_rate", vec![gears_obj!(3)]).unwrap(),
+        gears_obj!(15)
+    );
+    assert_eq!(
+        execute_function(&setup(), "get_total", vec![]).unwrap(),
+        gears_obj!(10)
+    );
+    assert_eq!(
+        execute_function(&setup(), "add_rate", vec![gears_obj!(1)]).unwrap(),
+        gears_obj!(6)
+    );
+    assert_eq!(
+        execute_function(&setup(), "joined_names", vec![]).unwrap(),
+        gears_obj!("ab")
+    );
+}
+
+#[test]
+fn test_write_globals() {
+    let module = compile_file("tests/files/globals.gs").unwrap();
+
+    disassemble(&module, "increment");
+    assert_eq!(
+        execute_function(&module, "increment", vec![]).unwrap(),
+        gears_obj!(1)
+    );
+    assert_eq!(
+        execute_function(&module, "increment", vec![]).unwrap(),
+        gears_obj!(2)
+    );
+    assert_eq!(module.get_global("counter").unwrap(), gears_obj!(2));
+
+    assert_eq!(
+        execute_function(&module, "last_name", vec![]).unwrap(),
+        gears_obj!("nobody")
+    );
+    execute_function(&module, "remember", vec![gears_obj!("gears")]).unwrap();
+    assert_eq!(
+        execute_function(&module, "last_name", vec![]).unwrap(),
+        gears_obj!("gears")
+    );
+
+    // The initializer sets every global back to its declared value
+    initialize_module(&module).unwrap();
+    assert_eq!(module.get_global("counter").unwrap(), gears_obj!(0));
+}
+
+#[test]
+fn test_host_globals() {
+    let module = compile_file("tests/files/globals.gs").unwrap();
+
+    assert_eq!(module.get_global("RATE").unwrap(), gears_obj!(5));
+    assert_eq!(
+        module.get_global("NAMES").unwrap(),
+        gears_obj!(list "a", "b")
+    );
+    assert_eq!(
+        module.get_global("missing"),
+        Err(GearsError::SymbolNotFound("missing".to_string()))
+    );
+
+    module.set_global("total", gears_obj!(42)).unwrap();
+    assert_eq!(
+        execute_function(&module, "get_total", vec![]).unwrap(),
+        gears_obj!(42)
+    );
+
+    module.set_global("last", gears_obj!("host")).unwrap();
+    module.set_global("last", Arc::new(GearsObject::None)).unwrap();
+    assert_eq!(
+        execute_function(&module, "last_name", vec![]).unwrap(),
+        gears_obj!("nobody")
+    );
+
+    match module.set_global("RATE", gears_obj!(1)) {
+        Err(GearsError::InterOpError {
+            error: InterOpErrorType::ConstGlobal,
+            ..
+        }) => {}
+        other => panic!("Expected a const global error, found {:?}", other),
+    }
+
+    match module.set_global("total", gears_obj!("ten")) {
+        Err(GearsError::InterOpError {
+            error: InterOpErrorType::WrongGlobalType,
+            ..
+        }) => {}
+        other => panic!("Expected a wrong global type error, found {:?}", other),
+    }
+
+    assert_eq!(module.get_global("RATE").unwrap(), gears_obj!(5));
+    assert_eq!(module.get_global("total").unwrap(), gears_obj!(42));
+}
+
+#[test]
+fn bad_globals() {
+    let bad = [
+        "const RATE: int = 5; def f() { RATE = 6; }",
+        "const RATE: int = \"five\";",
+        "let a: int = b; let b: int = 1;",
+        "def f() {} let f: int = 1;",
+        "let a: int = 1; let a: int = 2;",
+    ];
+
+    for source in bad.iter() {
+        assert!(
+            compile_str(source, "bad_globals").is_err(),
+            "{} should not compile",
+            source
+        );
+    }
+}
+
+#[test]
+fn uninitialized_globals() {
+    // Functions can read a global before the initializer gets to it
+    assert_eq!(
+        compile_str("let a: int = f(); def f() -> int { a + 1 }", "uninitialized").unwrap_err(),
+        GearsError::UninitializedGlobal("a".to_string())
+    );
+    assert_eq!(
+        compile_str(
+            "let a: int = f(); let b: int = 2; def f() -> int { b }",
+            "uninitialized"
+        ).unwrap_err(),
+        GearsError::UninitializedGlobal("b".to_string())
+    );
+
+    // Globals that are already set can be read
+    let module = compile_str(
+        "let b: int = 2; let a: int = f(); def f() -> int { b + 1 }",
+        "initialized",
+    ).unwrap();
+    assert_eq!(module.get_global("a").unwrap(), gears_obj!(3));
+}
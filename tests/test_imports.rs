@@ -0,0 +1,223 @@
+#[macro_use]
+extern crate gears_lang;
+#[macro_use]
+extern crate cached;
+#[macro_use]
+extern crate lazy_static;
+
+use gears_lang::compiler::{compile_file, compile_str, compile_with_resolver};
+use gears_lang::errors::GearsError;
+use gears_lang::module::{disassemble, Module};
+use gears_lang::object::GearsObject;
+use gears_lang::resolver::{FileResolver, MemoryResolver, ModuleResolver};
+use gears_lang::vm::execute_function;
+use std::sync::Arc;
+
+cached!{
+    FIB;
+    fn setup() -> Module = {
+        compile_file("tests/files/imports.gs").expect("Test failure")
+    }
+}
+
+#[test]
+fn test_imported_functions() {
+    disassemble(&setup(), "qualified_add");
+    assert_eq!(
+        execute_function(&setup(), "qualified_add", vec![gears_obj!(1), gears_obj!(2)]).unwrap(),
+        gears_obj!(3)
+    );
+    assert_eq!(
+        execute_function(&setup(), "imported_add", vec![gears_obj!(4), gears_obj!(5)]).unwrap(),
+        gears_obj!(9)
+    );
+    assert_eq!(
+        execute_function(&setup(), "apply", vec![gears_obj!(2)]).unwrap(),
+        gears_obj!(6)
+    );
+
+    // Imported functions can also be called by the host
+    assert_eq!(
+        execute_function(&setup(), "utils.scale", vec![gears_obj!(5)]).unwrap(),
+        gears_obj!(15)
+    );
+}
+
+#[test]
+fn test_imported_globals() {
+    let module = compile_file("tests/files/imports.gs").unwrap();
+
+    assert_eq!(
+        execute_function(&module, "scaled", vec![gears_obj!(2)]).unwrap(),
+        gears_obj!(9)
+    );
+
+    execute_function(&module, "imported_add", vec![gears_obj!(1), gears_obj!(1)]).unwrap();
+    execute_function(&module, "qualified_add", vec![gears_obj!(1), gears_obj!(1)]).unwrap();
+    assert_eq!(
+        execute_function(&module, "calls", vec![]).unwrap(),
+        gears_obj!(2)
+    );
+    assert_eq!(module.get_global("utils.calls").unwrap(), gears_obj!(2));
+    assert_eq!(module.get_global("utils.SCALE").unwrap(), gears_obj!(3));
+}
+
+#[test]
+fn test_imported_types() {
+    assert_eq!(
+        execute_function(&setup(), "moved_start", vec![gears_obj!(2)]).unwrap(),
+        gears_obj!(4)
+    );
+    assert_eq!(
+        execute_function(&setup(), "line_length", vec![gears_obj!(3)]).unwrap(),
+        gears_obj!(4)
+    );
+}
+
+#[test]
+fn test_types_per_module() {
+    let mut resolver = MemoryResolver::new();
+    resolver.add_module("a", "struct P { x: int } def make() -> P { P(1) }");
+    resolver.add_module("b", "struct P { x: int } def make() -> P { P(2) }");
+
+    // Each module has its own P, next to the one of the importing module
+    let module = compile_with_resolver(
+        r#"
+        import a;
+        import b;
+        struct P { x: int }
+
+        def sum() -> int {
+            let first: a.P = a.make();
+            let second: b.P = b.make();
+            let third: P = P(3);
+            first.x + second.x + third.x
+        }
+
+        def local(p: P) -> int { p.x }
+        "#,
+        "main",
+        &resolver,
+    ).unwrap();
+
+    assert_eq!(
+        execute_function(&module, "sum", vec![]).unwrap(),
+        gears_obj!(6)
+    );
+    assert_eq!(
+        execute_function(&module, "local", vec![gears_obj!(struct "P", "x" => 4)]).unwrap(),
+        gears_obj!(4)
+    );
+
+    let module = compile_with_resolver(
+        "from a import P, make; def f() -> int { let p: P = make(); p.x }",
+        "main",
+        &resolver,
+    ).unwrap();
+
+    assert_eq!(execute_function(&module, "f", vec![]).unwrap(), gears_obj!(1));
+
+    let bad = [
+        "import a; def f(p: P) -> int { p.x }",
+        "import a; def f() -> int { P(1).x }",
+        "import a; def f() -> a.Q { none }",
+        "import a; def f() -> a.make { none }",
+        "def f() -> a.P { none }",
+        "import a; import b; def f() -> a.P { b.make() }",
+        "from a import P; from b import P;",
+    ];
+
+    for source in bad.iter() {
+        assert!(
+            compile_with_resolver(source, "bad_types", &resolver).is_err(),
+            "{} should not compile",
+            source
+        );
+    }
+}
+
+#[test]
+fn test_memory_resolver() {
+    let mut resolver = MemoryResolver::new();
+    resolver.add_module("math", "def double(x: int) -> int { x * 2 }");
+
+    let module = compile_with_resolver(
+        "from math import double; def quad(x: int) -> int { double(double(x)) }",
+        "main",
+        &resolver,
+    ).unwrap();
+
+    assert_eq!(
+        execute_function(&module, "quad", vec![gears_obj!(3)]).unwrap(),
+        gears_obj!(12)
+    );
+
+    let file_resolver = FileResolver::new("tests/files");
+    assert!(file_resolver.resolve("utils").is_ok());
+    assert_eq!(
+        file_resolver.resolve("missing").unwrap_err(),
+        GearsError::ModuleNotFound("missing".to_string())
+    );
+}
+
+#[test]
+fn test_import_cycles() {
+    let mut resolver = MemoryResolver::new();
+    resolver.add_module("a", "import b; def f() -> int { 1 }");
+    resolver.add_module("b", "import c; def g() -> int { 2 }");
+    resolver.add_module("c", "import a; def h() -> int { 3 }");
+
+    assert_eq!(
+        compile_with_resolver("import a;", "main", &resolver).unwrap_err(),
+        GearsError::ImportError("Import cycle: a -> b -> c -> a".to_string())
+    );
+    assert_eq!(
+        compile_with_resolver("import a;", "c", &resolver).unwrap_err(),
+        GearsError::ImportError("Import cycle: c -> a -> b -> c".to_string())
+    );
+
+    // Importing a module from two places is not a cycle
+    resolver.add_module("c", "def h() -> int { 3 }");
+    resolver.add_module("d", "import c; def k() -> int { c.h() }");
+    assert!(compile_with_resolver("import c; import d;", "main", &resolver).is_ok());
+}
+
+#[test]
+fn bad_imports() {
+    let mut resolver = MemoryResolver::new();
+    resolver.add_module("math", "def double(x: int) -> int { x * 2 } const TEN: int = 10;");
+
+    let bad = [
+        "import missing;",
+        "from math import triple;",
+        "import math; def f() -> int { math.triple(1) }",
+        "import math; def f() -> str { math.double(1) }",
+        "from math import double; def f() -> str { double(1) }",
+        "import math; def f() { math.TEN = 1; }",
+        "from math import TEN; def f() { TEN = 1; }",
+        "import math; def f() -> int { math }",
+        "from math import double; def double() {}",
+    ];
+
+    for source in bad.iter() {
+        assert!(
+            compile_with_resolver(source, "bad_imports", &resolver).is_err(),
+            "{} should not compile",
+            source
+        );
+    }
+
+    assert!(compile_str("import math;", "no_imports").is_err());
+
+    // Calls to an imported function are checked like local calls
+    assert_eq!(
+        compile_with_resolver("import math; def f() -> int { math.double(1, 2) }", "arity", &resolver)
+            .unwrap_err(),
+        GearsError::TypeError("double takes 1 args, 2 were given".to_string())
+    );
+    assert_eq!(
+        compile_with_resolver("from math import double; def f() -> int { double() }", "arity", &resolver)
+            .unwrap_err(),
+        GearsError::TypeError("double is missing the arg x".to_string())
+    );
+}
@@ -0,0 +1,157 @@
+#[macro_use]
+extern crate gears_lang;
+#[macro_use]
+extern crate cached;
+#[macro_use]
+extern crate lazy_static;
+
+use gears_lang::compiler::compile_file;
+use gears_lang::errors::GearsError;
+use gears_lang::module::{disassemble, Module};
+use gears_lang::object::{GearsObject, FALSE_OBJ, TRUE_OBJ};
+use gears_lang::vm::execute_function;
+use std::sync::Arc;
+
+cached!{
+    FIB;
+    fn setup() -> Module = {
+        compile_file("tests/files/lists.gs").expect("Test failure")
+    }
+}
+
+#[test]
+fn test_simple_list() {
+    disassemble(&setup(), "simple_list");
+    assert_eq!(
+        execute_function(&setup(), "simple_list", vec![]).unwrap(),
+        gears_obj!(list 1, 2, 3)
+    );
+}
+
+#[test]
+fn test_expr_list() {
+    assert_eq!(
+        execute_function(&setup(), "expr_list", vec![gears_obj!(2)]).unwrap(),
+        gears_obj!(list 2, 6, 8)
+    );
+}
+
+#[test]
+fn test_mixed_list() {
+    assert_eq!(
+        execute_function(&setup(), "mixed_list", vec![]).unwrap(),
+        gears_obj!(list 1, 5, true)
+    );
+}
+
+#[test]
+fn test_nested_list() {
+    assert_eq!(
+        execute_function(&setup(), "nested_list", vec![]).unwrap(),
+        Arc::new(GearsObject::List(vec![
+            gears_obj!(list 1, 2),
+            gears_obj!(list),
+            gears_obj!("three"),
+        ]))
+    );
+}
+
+#[test]
+fn test_empty_list() {
+    assert_eq!(
+        execute_function(&setup(), "empty_list", vec![]).unwrap(),
+        gears_obj!(list)
+    );
+}
+
+#[test]
+fn test_list_args() {
+    assert_eq!(
+        execute_function(&setup(), "pass_list", vec![gears_obj!(list 4, 5)]).unwrap(),
+        gears_obj!(list 4, 5)
+    );
+    assert_eq!(
+        execute_function(
+            &setup(),
+            "compare_lists",
+            vec![gears_obj!(list 1, 2), gears_obj!(list 1, 2)]
+        ).unwrap(),
+        TRUE_OBJ.clone()
+    );
+    assert_eq!(
+        execute_function(
+            &setup(),
+            "compare_lists",
+            vec![gears_obj!(list 1, 2), gears_obj!(list 2, 1)]
+        ).unwrap(),
+        FALSE_OBJ.clone()
+    );
+}
+
+#[test]
+fn test_index() {
+    let list = gears_obj!(list 1, 2, 3);
+
+    for (index, expected) in vec![(0, 1), (2, 3), (-1, 3), (-3, 1)] {
+        assert_eq!(
+            execute_function(&setup(), "index_list", vec![list.clone(), gears_obj!(index)])
+                .unwrap(),
+            gears_obj!(expected)
+        );
+    }
+
+    assert_eq!(
+        execute_function(&setup(), "index_str", vec![gears_obj!("gears"), gears_obj!(-2)])
+            .unwrap(),
+        gears_obj!("r")
+    );
+    assert_eq!(
+        execute_function(&setup(), "nested_index", vec![]).unwrap(),
+        gears_obj!(15)
+    );
+}
+
+#[test]
+fn test_index_out_of_range() {
+    for index in vec![3, -4] {
+        match execute_function(
+            &setup(),
+            "index_list",
+            vec![gears_obj!(list 1, 2, 3), gears_obj!(index)],
+        ) {
+            Err(GearsError::IndexError(_)) => {}
+            other => panic!("Index {} returned {:?}", index, other),
+        }
+    }
+
+    match execute_function(&setup(), "index_str", vec![gears_obj!(""), gears_obj!(0)]) {
+        Err(GearsError::IndexError(_)) => {}
+        other => panic!("Empty string index returned {:?}", other),
+    }
+}
+
+#[test]
+fn test_slice() {
+    let list = gears_obj!(list 1, 2, 3, 4);
+
+    for (start, end, expected) in vec![
+        (1, 3, gears_obj!(list 2, 3)),
+        (0, 10, gears_obj!(list 1, 2, 3, 4)),
+        (-2, 4, gears_obj!(list 3, 4)),
+        (3, 1, gears_obj!(list)),
+    ] {
+        assert_eq!(
+            execute_function(
+                &setup(),
+                "slice_list",
+                vec![list.clone(), gears_obj!(start), gears_obj!(end)]
+            ).unwrap(),
+            expected
+        );
+    }
+
+    assert_eq!(
+        execute_function(&setup(), "slice_str", vec![gears_obj!("gears")]).unwrap(),
+        gears_obj!(list "ea", "ge", "ars", "gears", "ar")
+    );
+}
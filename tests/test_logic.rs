@@ -0,0 +1,112 @@
+#[macro_use]
+extern crate gears_lang;
+#[macro_use]
+extern crate cached;
+#[macro_use]
+extern crate lazy_static;
+
+use gears_lang::compiler::compile_file;
+use gears_lang::module::Module;
+use gears_lang::object::{GearsObject, FALSE_OBJ, TRUE_OBJ};
+use gears_lang::vm::execute_function;
+use std::sync::Arc;
+
+cached!{
+    FIB;
+    fn setup() -> Module = {
+        compile_file("tests/files/logic.gs").expect("Test failure")
+    }
+}
+
+#[test]
+fn test_truth_tables() {
+    let test_set = vec![
+        // a, b, and, or
+        (true, true, true, true),
+        (true, false, false, true),
+        (false, true, false, true),
+        (false, false, false, false),
+    ];
+
+    for row in test_set {
+        let a = gears_obj!(row.0);
+        let b = gears_obj!(row.1);
+
+        assert_eq!(
+            execute_function(&setup(), "logic_and", vec![a.clone(), b.clone()]).unwrap(),
+            gears_obj!(row.2),
+            "Failed for: {} and {}",
+            row.0,
+            row.1
+        );
+
+        assert_eq!(
+            execute_function(&setup(), "logic_or", vec![a.clone(), b.clone()]).unwrap(),
+            gears_obj!(row.3),
+            "Failed for: {} or {}",
+            row.0,
+            row.1
+        );
+    }
+}
+
+#[test]
+fn test_not() {
+    assert_eq!(
+        execute_function(&setup(), "logic_not", vec![TRUE_OBJ.clone()]).unwrap(),
+        FALSE_OBJ.clone()
+    );
+    assert_eq!(
+        execute_function(&setup(), "logic_not", vec![FALSE_OBJ.clone()]).unwrap(),
+        TRUE_OBJ.clone()
+    );
+}
+
+#[test]
+fn test_precedence() {
+    assert_eq!(
+        execute_function(&setup(), "guard", vec![gears_obj!(3), gears_obj!(4)]).unwrap(),
+        TRUE_OBJ.clone()
+    );
+    assert_eq!(
+        execute_function(&setup(), "guard", vec![gears_obj!(3), gears_obj!(12)]).unwrap(),
+        FALSE_OBJ.clone()
+    );
+    assert_eq!(
+        execute_function(&setup(), "guard", vec![gears_obj!(5), gears_obj!(12)]).unwrap(),
+        TRUE_OBJ.clone()
+    );
+}
+
+#[test]
+fn test_truthiness() {
+    assert_eq!(
+        execute_function(&setup(), "truthy", vec![gears_obj!(1), gears_obj!("a")]).unwrap(),
+        TRUE_OBJ.clone()
+    );
+    assert_eq!(
+        execute_function(&setup(), "truthy", vec![gears_obj!(1), gears_obj!("")]).unwrap(),
+        FALSE_OBJ.clone()
+    );
+}
+
+#[test]
+fn test_short_circuit() {
+    assert!(execute_function(&setup(), "boom", vec![gears_obj!("x")]).is_err());
+    assert_eq!(
+        execute_function(&setup(), "and_short_circuit", vec![]).unwrap(),
+        FALSE_OBJ.clone()
+    );
+    assert_eq!(
+        execute_function(&setup(), "or_short_circuit", vec![]).unwrap(),
+        TRUE_OBJ.clone()
+    );
+}
+
+#[test]
+fn test_while_and() {
+    assert_eq!(
+        execute_function(&setup(), "while_and", vec![]).unwrap(),
+        gears_obj!(4)
+    );
+}
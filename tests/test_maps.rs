@@ -0,0 +1,151 @@
+#[macro_use]
+extern crate gears_lang;
+#[macro_use]
+extern crate cached;
+#[macro_use]
+extern crate lazy_static;
+
+use gears_lang::compiler::compile_file;
+use gears_lang::errors::GearsError;
+use gears_lang::module::{disassemble, Module};
+use gears_lang::object::{GearsObject, FALSE_OBJ, NONE_OBJ, TRUE_OBJ};
+use gears_lang::vm::execute_function;
+use std::sync::Arc;
+
+cached!{
+    FIB;
+    fn setup() -> Module = {
+        compile_file("tests/files/maps.gs").expect("Test failure")
+    }
+}
+
+#[test]
+fn test_simple_map() {
+    disassemble(&setup(), "simple_map");
+    assert_eq!(
+        execute_function(&setup(), "simple_map", vec![]).unwrap(),
+        gears_obj!(map "one" => 1, "two" => 2, "three" => 3)
+    );
+    assert_eq!(
+        execute_function(&setup(), "empty_map", vec![]).unwrap(),
+        gears_obj!(map)
+    );
+    assert_eq!(
+        execute_function(&setup(), "mixed_map", vec![]).unwrap(),
+        Arc::new(GearsObject::Map(vec![
+            (gears_obj!(1), gears_obj!(list 1, 2)),
+            (gears_obj!(true), gears_obj!("yes")),
+        ]))
+    );
+}
+
+#[test]
+fn test_lookup() {
+    let map = gears_obj!(map "a" => 1, "b" => 2);
+
+    assert_eq!(
+        execute_function(&setup(), "lookup", vec![map.clone(), gears_obj!("b")]).unwrap(),
+        gears_obj!(2)
+    );
+    assert_eq!(
+        execute_function(&setup(), "nested_lookup", vec![]).unwrap(),
+        gears_obj!(8)
+    );
+
+    match execute_function(&setup(), "lookup", vec![map, gears_obj!("c")]) {
+        Err(GearsError::KeyError(_)) => {}
+        other => panic!("Missing key returned {:?}", other),
+    }
+}
+
+#[test]
+fn test_insert() {
+    let map = gears_obj!(map "a" => 1, "b" => 2);
+
+    assert_eq!(
+        execute_function(
+            &setup(),
+            "insert",
+            vec![map.clone(), gears_obj!("c"), gears_obj!(3)]
+        ).unwrap(),
+        gears_obj!(map "a" => 1, "b" => 2, "c" => 3)
+    );
+    // Updating a key keeps its original position
+    assert_eq!(
+        execute_function(&setup(), "insert", vec![map.clone(), gears_obj!("a"), gears_obj!(5)])
+            .unwrap(),
+        gears_obj!(map "a" => 5, "b" => 2)
+    );
+    assert_eq!(
+        execute_function(&setup(), "copy_on_write", vec![map]).unwrap(),
+        FALSE_OBJ.clone()
+    );
+    assert_eq!(
+        execute_function(&setup(), "update_list", vec![gears_obj!(list 1, 2, 3)]).unwrap(),
+        gears_obj!(list 2, 2, 10)
+    );
+    assert_eq!(
+        execute_function(&setup(), "update_nested", vec![]).unwrap(),
+        gears_obj!(9)
+    );
+}
+
+#[test]
+fn test_membership() {
+    let map = gears_obj!(map "a" => 1);
+
+    assert_eq!(
+        execute_function(&setup(), "has_key", vec![map.clone(), gears_obj!("a")]).unwrap(),
+        TRUE_OBJ.clone()
+    );
+    assert_eq!(
+        execute_function(&setup(), "missing_key", vec![map, gears_obj!("a")]).unwrap(),
+        FALSE_OBJ.clone()
+    );
+    assert_eq!(
+        execute_function(&setup(), "membership", vec![]).unwrap(),
+        gears_obj!(list true, false, true, true)
+    );
+}
+
+#[test]
+fn test_iteration() {
+    let map = gears_obj!(map "z" => 1, "a" => 2, "m" => 3);
+
+    assert_eq!(
+        execute_function(&setup(), "sum_values", vec![map.clone()]).unwrap(),
+        gears_obj!(6)
+    );
+    assert_eq!(
+        execute_function(&setup(), "first_key", vec![map]).unwrap(),
+        gears_obj!("z")
+    );
+    assert_eq!(
+        execute_function(&setup(), "first_key", vec![gears_obj!(map)]).unwrap(),
+        NONE_OBJ.clone()
+    );
+}
+
+#[test]
+fn test_invalid_assignment_target() {
+    use gears_lang::compiler::compile_str;
+
+    for string in vec![
+        "def test() { 1 = 2 }",
+        "def test(a: [[int]]) { a[0:1][0] = 2 }",
+        "def test(a: [int]) { a[0:1] = 2 }",
+    ] {
+        match compile_str(string, "test") {
+            // The location is where the target starts
+            Err(GearsError::ParseError { location, message }) => {
+                let column = string.find("{ ").unwrap() + 3;
+                assert_eq!(location.column(), column, "{}", string);
+                assert_eq!(
+                    message,
+                    format!("Invalid assignment target at Line: 1, Char: {}", column)
+                );
+            }
+            other => panic!("{} returned {:?}", string, other.map(|_| ())),
+        }
+    }
+}
@@ -2,6 +2,7 @@
 extern crate gears_lang;
 
 use gears_lang::compiler::compile_file;
+use gears_lang::errors::GearsError;
 use gears_lang::object::GearsObject;
 use gears_lang::vm::execute_function;
 use std::sync::Arc;
@@ -20,3 +21,190 @@ fn math() {
         gears_obj!(4 + 3 * 5 - 42 / 6)
     );
 }
+
+#[test]
+fn float_math() {
+    let module = compile_file("tests/files/math.gs").expect("Test");
+
+    assert_eq!(
+        execute_function(&module, "float_math", vec![]).unwrap(),
+        gears_obj!(8.5)
+    );
+}
+
+#[test]
+fn mixed_math() {
+    let module = compile_file("tests/files/math.gs").expect("Test");
+
+    assert_eq!(
+        execute_function(&module, "mixed_math", vec![gears_obj!(2), gears_obj!(0.25)]).unwrap(),
+        gears_obj!(2.25)
+    );
+}
+
+#[test]
+fn float_compare() {
+    let module = compile_file("tests/files/math.gs").expect("Test");
+
+    assert_eq!(
+        execute_function(&module, "float_compare", vec![gears_obj!(2.5), gears_obj!(3)]).unwrap(),
+        gears_obj!(true)
+    );
+    assert_eq!(
+        execute_function(&module, "float_compare", vec![gears_obj!(3.0), gears_obj!(3)]).unwrap(),
+        gears_obj!(false)
+    );
+}
+
+#[test]
+fn unary_math() {
+    let module = compile_file("tests/files/math.gs").expect("Test");
+
+    assert_eq!(
+        execute_function(&module, "negate", vec![gears_obj!(5)]).unwrap(),
+        gears_obj!(-5)
+    );
+    assert_eq!(
+        execute_function(&module, "unary_math", vec![gears_obj!(4)]).unwrap(),
+        gears_obj!(-1)
+    );
+    assert_eq!(
+        execute_function(&module, "negative_float", vec![]).unwrap(),
+        gears_obj!(5.0)
+    );
+    assert_eq!(
+        execute_function(&module, "min_int", vec![]).unwrap(),
+        gears_obj!(i64::min_value())
+    );
+}
+
+#[test]
+fn negate_type_error() {
+    let module = compile_file("tests/files/math.gs").expect("Test");
+
+    assert!(execute_function(&module, "negate", vec![gears_obj!("text")]).is_err());
+}
+
+#[test]
+fn division_type_error() {
+    let text = gears_obj!("a");
+    let zero = gears_obj!(0);
+
+    // The types are checked before the divisor
+    for result in vec![text.div(&zero), text.floor_div(&zero), text.modulo(&zero)] {
+        match result {
+            Err(GearsError::TypeError(_)) => {}
+            other => panic!("Dividing a str returned {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn extended_math() {
+    let module = compile_file("tests/files/math.gs").expect("Test");
+
+    let test_set = vec![
+        // a, b, %, //
+        (7, 2, 1, 3),
+        (-7, 2, 1, -4),
+        (7, -2, -1, -4),
+        (-7, -2, -1, 3),
+    ];
+
+    for row in test_set {
+        assert_eq!(
+            execute_function(&module, "modulo", vec![gears_obj!(row.0), gears_obj!(row.1)])
+                .unwrap(),
+            gears_obj!(row.2),
+            "Failed for: {} % {}",
+            row.0,
+            row.1
+        );
+        assert_eq!(
+            execute_function(&module, "floor_div", vec![gears_obj!(row.0), gears_obj!(row.1)])
+                .unwrap(),
+            gears_obj!(row.3),
+            "Failed for: {} // {}",
+            row.0,
+            row.1
+        );
+    }
+
+    assert_eq!(
+        execute_function(&module, "power", vec![gears_obj!(3), gears_obj!(4)]).unwrap(),
+        gears_obj!(81)
+    );
+
+    // Exponents past u32 only fit for the bases that never grow
+    let huge = 5_000_000_000;
+    for (a, b, result) in vec![
+        (1, huge, 1),
+        (0, huge, 0),
+        (-1, huge, 1),
+        (-1, huge + 1, -1),
+        (0, 0, 1),
+    ] {
+        assert_eq!(
+            execute_function(&module, "power", vec![gears_obj!(a), gears_obj!(b)]).unwrap(),
+            gears_obj!(result),
+            "Failed for: {} ** {}",
+            a,
+            b
+        );
+    }
+    // -(2 ** 2) + ((2 ** (3 ** 2)) % 7) * 3 // 2
+    assert_eq!(
+        execute_function(&module, "precedence", vec![]).unwrap(),
+        gears_obj!(-4 + (512 % 7) * 3 / 2)
+    );
+    assert_eq!(
+        execute_function(&module, "float_ops", vec![gears_obj!(5.5), gears_obj!(2.0)]).unwrap(),
+        gears_obj!(1.5 + 2.0 + 30.25)
+    );
+}
+
+#[test]
+fn arithmetic_errors() {
+    let module = compile_file("tests/files/math.gs").expect("Test");
+
+    for function in &["modulo", "floor_div", "divide"] {
+        match execute_function(&module, function, vec![gears_obj!(1), gears_obj!(0)]) {
+            Err(GearsError::ArithmeticError(_)) => {}
+            other => panic!("{} by zero returned {:?}", function, other),
+        }
+    }
+
+    match execute_function(&module, "power", vec![gears_obj!(2), gears_obj!(64)]) {
+        Err(GearsError::ArithmeticError(_)) => {}
+        other => panic!("Overflow returned {:?}", other),
+    }
+
+    match execute_function(&module, "power", vec![gears_obj!(2), gears_obj!(5_000_000_000)]) {
+        Err(GearsError::ArithmeticError(_)) => {}
+        other => panic!("Overflow past u32 returned {:?}", other),
+    }
+
+    match execute_function(&module, "power", vec![gears_obj!(2), gears_obj!(-1)]) {
+        Err(GearsError::ArithmeticError(_)) => {}
+        other => panic!("Negative exponent returned {:?}", other),
+    }
+
+    let max = i64::max_value();
+    let min = i64::min_value();
+
+    for (function, a, b) in vec![
+        ("add", max, 1),
+        ("add", min, -1),
+        ("subtract", min, 1),
+        ("subtract", max, -1),
+        ("multiply", max, 2),
+        ("multiply", min, -1),
+        ("divide", min, -1),
+        ("floor_div", min, -1),
+    ] {
+        match execute_function(&module, function, vec![gears_obj!(a), gears_obj!(b)]) {
+            Err(GearsError::ArithmeticError(_)) => {}
+            other => panic!("{} overflow returned {:?}", function, other),
+        }
+    }
+}
@@ -5,8 +5,9 @@ extern crate cached;
 #[macro_use]
 extern crate lazy_static;
 
-use gears_lang::compiler::compile_file;
-use gears_lang::module::Module;
+use gears_lang::compiler::{compile_file, compile_str};
+use gears_lang::errors::GearsError;
+use gears_lang::module::{disassemble, Module};
 use gears_lang::object::GearsObject;
 use gears_lang::vm::execute_function;
 use std::sync::Arc;
@@ -25,3 +26,55 @@ fn return_string() {
         gears_obj!("simple string")
     );
 }
+
+#[test]
+fn string_escapes() {
+    assert_eq!(
+        execute_function(&setup(), "escapes", vec![]).unwrap(),
+        gears_obj!("tab:\t quote:\" slash:\\ \u{263A}\nend")
+    );
+    assert_eq!(
+        execute_function(&setup(), "empty_string", vec![]).unwrap(),
+        gears_obj!("")
+    );
+
+    for string in vec![r#"def test() -> str { "\q" }"#, r#"def test() -> str { "abc }"#] {
+        match compile_str(string, "test") {
+            Err(GearsError::ParseError { .. }) => {}
+            other => panic!("{} returned {:?}", string, other.map(|_| ())),
+        }
+    }
+}
+
+#[test]
+fn format_strings() {
+    disassemble(&setup(), "total");
+    assert_eq!(
+        execute_function(&setup(), "total", vec![gears_obj!(2), gears_obj!(3)]).unwrap(),
+        gears_obj!("total: 5")
+    );
+    assert_eq!(
+        execute_function(
+            &setup(),
+            "describe",
+            vec![
+                gears_obj!("gears"),
+                gears_obj!(list 1, 2),
+                gears_obj!(2.0),
+                gears_obj!(true),
+            ]
+        ).unwrap(),
+        gears_obj!("gears has [1, 2] at 2.0, done: true {none}")
+    );
+    assert_eq!(
+        execute_function(
+            &setup(),
+            "nested_format",
+            vec![Arc::new(GearsObject::Map(vec![(
+                gears_obj!("a"),
+                gears_obj!(tuple 1, "b"),
+            )]))]
+        ).unwrap(),
+        gears_obj!("[\"a\": (1, \"b\")] a}")
+    );
+}
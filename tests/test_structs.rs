@@ -0,0 +1,112 @@
+#[macro_use]
+extern crate gears_lang;
+#[macro_use]
+extern crate cached;
+#[macro_use]
+extern crate lazy_static;
+
+use gears_lang::compiler::{compile_file, compile_str};
+use gears_lang::errors::GearsError;
+use gears_lang::module::{disassemble, Module};
+use gears_lang::object::{GearsObject, FALSE_OBJ, NONE_OBJ, TRUE_OBJ};
+use gears_lang::vm::execute_function;
+use std::sync::Arc;
+
+cached!{
+    FIB;
+    fn setup() -> Module = {
+        compile_file("tests/files/structs.gs").expect("Test failure")
+    }
+}
+
+#[test]
+fn test_constructor() {
+    disassemble(&setup(), "new_point");
+    assert_eq!(
+        execute_function(&setup(), "new_point", vec![gears_obj!(1), gears_obj!(2)]).unwrap(),
+        gears_obj!(struct "Point", "x" => 1, "y" => 2)
+    );
+    assert_eq!(
+        setup().get_struct("Point").unwrap().get_fields(),
+        &vec!["x".to_string(), "y".to_string()]
+    );
+}
+
+#[test]
+fn test_field_read() {
+    let point = gears_obj!(struct "Point", "x" => 5, "y" => 2);
+
+    assert_eq!(
+        execute_function(&setup(), "get_x", vec![point]).unwrap(),
+        gears_obj!(5)
+    );
+
+    let line = execute_function(&setup(), "make_line", vec![]).unwrap();
+    assert_eq!(line.get_field("label").unwrap(), NONE_OBJ.clone());
+    assert_eq!(
+        execute_function(&setup(), "length_squared", vec![line]).unwrap(),
+        gears_obj!(25)
+    );
+}
+
+#[test]
+fn test_field_write() {
+    let point = gears_obj!(struct "Point", "x" => 5, "y" => 2);
+
+    assert_eq!(
+        execute_function(&setup(), "move_point", vec![point.clone(), gears_obj!(3)]).unwrap(),
+        gears_obj!(struct "Point", "x" => 8, "y" => 2)
+    );
+    assert_eq!(
+        execute_function(&setup(), "copy_on_write", vec![point.clone()]).unwrap(),
+        FALSE_OBJ.clone()
+    );
+    assert_eq!(
+        execute_function(&setup(), "points_equal", vec![point.clone(), point]).unwrap(),
+        TRUE_OBJ.clone()
+    );
+
+    // Fields and indexes can be chained, every container on the way is copied
+    let line = execute_function(&setup(), "make_line", vec![]).unwrap();
+    assert_eq!(
+        execute_function(&setup(), "move_end", vec![line.clone(), gears_obj!(2)]).unwrap(),
+        gears_obj!(6)
+    );
+    assert_eq!(
+        execute_function(&setup(), "relabel", vec![gears_obj!("b")]).unwrap(),
+        Arc::new(GearsObject::List(vec![NONE_OBJ.clone(), gears_obj!("b")]))
+    );
+    assert_eq!(
+        execute_function(&setup(), "nested_copy_on_write", vec![line]).unwrap(),
+        FALSE_OBJ.clone()
+    );
+}
+
+#[test]
+fn test_bad_structs() {
+    let structs = "struct Point { x: int, y: int }\n";
+
+    for string in vec![
+        "def test() -> Point { Point(1) }",
+        "def test() -> Point { Point(1, 2, 3) }",
+        "def test() -> Point { Point(1, \"2\") }",
+        "def test(p: Point) -> int { p.z }",
+        "def test(p: Point) -> str { p.x }",
+        "def test(p: Point) { p.x = \"a\" }",
+        "def test(p: Point) { p.z = 1 }",
+        "def test(p: Point) { p.x.y = 1 }",
+        "def test(l: [Point]) { l[0].z = 1 }",
+        "def test(l: [Point]) { l[0].x = \"a\" }",
+        "def test(l: [Point]) { l[\"a\"].x = 1 }",
+        "def test(l: [Point] | Point) { l[0].x = 1 }",
+        "def test(p: int) -> int { p.x }",
+        "def test() -> int { Point(1, 2) }",
+        "struct Twice { a: int, a: int }",
+        "struct int { a: int }",
+    ] {
+        match compile_str(&format!("{}{}", structs, string), "test") {
+            Err(GearsError::TypeError(_)) => {}
+            other => panic!("{} returned {:?}", string, other.map(|_| ())),
+        }
+    }
+}
@@ -0,0 +1,110 @@
+#[macro_use]
+extern crate gears_lang;
+#[macro_use]
+extern crate cached;
+#[macro_use]
+extern crate lazy_static;
+
+use gears_lang::compiler::{compile_file, compile_str};
+use gears_lang::errors::GearsError;
+use gears_lang::module::{disassemble, Module};
+use gears_lang::object::GearsObject;
+use gears_lang::vm::execute_function;
+use std::sync::Arc;
+
+cached!{
+    FIB;
+    fn setup() -> Module = {
+        compile_file("tests/files/tuples.gs").expect("Test failure")
+    }
+}
+
+#[test]
+fn test_tuple_literals() {
+    assert_eq!(
+        execute_function(&setup(), "pair", vec![gears_obj!(1), gears_obj!("a")]).unwrap(),
+        gears_obj!(tuple 1, "a")
+    );
+    assert_eq!(
+        execute_function(&setup(), "single", vec![]).unwrap(),
+        gears_obj!(tuple 1)
+    );
+    assert_eq!(
+        execute_function(&setup(), "nested", vec![]).unwrap(),
+        Arc::new(GearsObject::Tuple(vec![
+            gears_obj!(tuple 1, true),
+            gears_obj!(list 2, 3),
+        ]))
+    );
+}
+
+#[test]
+fn test_destructure() {
+    disassemble(&setup(), "destructure");
+    assert_eq!(
+        execute_function(&setup(), "destructure", vec![gears_obj!(17), gears_obj!(5)]).unwrap(),
+        gears_obj!(302)
+    );
+    assert_eq!(
+        execute_function(&setup(), "destructure_nested", vec![]).unwrap(),
+        gears_obj!(123)
+    );
+    assert_eq!(
+        execute_function(&setup(), "swap", vec![gears_obj!(1), gears_obj!(2)]).unwrap(),
+        gears_obj!(tuple 2, 1)
+    );
+}
+
+#[test]
+fn test_tuple_index() {
+    assert_eq!(
+        execute_function(&setup(), "index_tuple", vec![gears_obj!(tuple 4, "a")]).unwrap(),
+        gears_obj!(5)
+    );
+}
+
+#[test]
+fn test_for_destructure() {
+    assert_eq!(
+        execute_function(
+            &setup(),
+            "sum_pairs",
+            vec![Arc::new(GearsObject::List(vec![
+                gears_obj!(tuple 1, 2),
+                gears_obj!(tuple 3, 4),
+            ]))]
+        ).unwrap(),
+        gears_obj!(14)
+    );
+}
+
+#[test]
+fn test_shadowing_slots() {
+    assert_eq!(
+        execute_function(&setup(), "shadow", vec![]).unwrap(),
+        gears_obj!(12)
+    );
+}
+
+#[test]
+fn test_bad_destructure() {
+    for string in vec![
+        "def test() { let (a, b): (int, int, int) = (1, 2, 3); }",
+        "def test() { let (a, b): [int] = [1, 2]; }",
+        "def test() { for (a, b) in [1, 2] { a } }",
+        "def test() { for (a, b) in 0 to 2 { a } }",
+        "def test() -> str { let (a, b): (int, str) = (1, \"a\"); a }",
+        "def test(t: (int, str)) -> int { t[1] }",
+        "def test(t: (int, str)) { t[2] }",
+    ] {
+        match compile_str(string, "test") {
+            Err(GearsError::TypeError(_)) => {}
+            other => panic!("{} returned {:?}", string, other.map(|_| ())),
+        }
+    }
+
+    match compile_str("def test() { let (a, a): (int, int) = (1, 2); }", "test") {
+        Err(GearsError::TypeError(_)) => {}
+        other => panic!("Repeated name returned {:?}", other.map(|_| ())),
+    }
+}
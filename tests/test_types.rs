@@ -12,8 +12,59 @@ fn bad_typing() {
         r#"def test() -> int { let a: int = true }"#,
         r#"def test() -> str { 1 }"#,
         r#"def test() -> bool { 1 }"#,
-        r#"def test() -> list { 1 }"#,
+        r#"def test() -> [int] { 1 }"#,
+        r#"def test() -> float { 1 }"#,
+        r#"def test() -> int { 1.5 }"#,
+        r#"def test() -> int { 2 * 1.5 }"#,
+        r#"def test() -> int { -true }"#,
+        r#"def test() -> str { +"test" }"#,
+        r#"def test() -> [int] { [1, true] }"#,
+        r#"def test() -> [int] { let a: [bool] = [1]; a }"#,
+        r#"def test() -> [int] { let a: [int | bool] = [1]; a }"#,
+        r#"def test() -> int { [1] }"#,
+        r#"def test() -> [[int]] { [1] }"#,
+        r#"def test() -> int { [1, 2][true] }"#,
+        r#"def test() -> int { 5[0] }"#,
+        r#"def test() -> int { [true][0] }"#,
+        r#"def test() -> int { "test"[0] }"#,
+        r#"def test() -> [int] { [1, 2]["a":] }"#,
+        r#"def test() -> str { [1, 2][1:] }"#,
+        r#"def test() { for x in 1 to true {} }"#,
+        r#"def test() { for x in 1 to 5 step 1.5 {} }"#,
+        r#"def test() { for x in 5 {} }"#,
+        r#"def test() { for x in ["a"] { x + 1 } }"#,
+        r#"def test() -> int { return "test"; }"#,
+        r#"def test(a: bool) -> int { if a { return 1; }; "test" }"#,
+        r#"def test(a: bool) -> int { if a { return true; }; 1 }"#,
+        r#"def test() { return 1; }"#,
+        r#"def test() -> int { return; }"#,
+        r#"def test() -> {str: int} { ["a": "b"] }"#,
+        r#"def test() -> {str: int} { [1: 1] }"#,
+        r#"def test(a: {float: int}) { a }"#,
+        r#"def test() { [[1]: 2] }"#,
+        r#"def test(a: {str: int}) -> int { a[1] }"#,
+        r#"def test(a: {str: int}) -> str { a["b"] }"#,
+        r#"def test(a: {str: int}) { a["b"] = "c" }"#,
+        r#"def test(a: {str: int}) { a[1] = 2 }"#,
+        r#"def test(a: str) { a[1] = "b" }"#,
+        r#"def test(a: {str: int}) { 1 in a }"#,
+        r#"def test(a: [str]) { 1 in a }"#,
+        r#"def test(a: int) { 1 in a }"#,
         r#"def add(a: int) -> int { 2 + a } def test() -> int { add(1); }"#,
+        r#"def test(a: int | none) -> int { a + 1 }"#,
+        r#"def test(a: int | none) -> int { if a == none { a + 1 } else { 0 } }"#,
+        r#"def test(a: int | none) -> int { if a != none { 0 }; a + 1 }"#,
+        r#"def test(a: int | none) -> int { if a == none { 0 } else { 1 }; a }"#,
+        r#"def test(a: int | none) -> bool { a != none or a + 1 > 1 }"#,
+        r#"def test(a: int | none) -> int { match a { 0 => 0, _ => a } }"#,
+        r#"def test(a: int | none) -> int { match a { none => 0, _ => 1, _ => 2 } }"#,
+        r#"def test(a: int) -> int { match a { none => 0, _ => 1 } }"#,
+        r#"def add(a: int, b: int) -> int { a + b } def test() -> int { add(1) }"#,
+        r#"def add(a: int, b: int) -> int { a + b } def test() -> int { add(1, 2, 3) }"#,
+        r#"def add(a: int, b: int) -> int { a + b } def test() -> int { add(1, "2") }"#,
+        r#"def add(a: int, b: int) -> int { a + b } def test() -> int { add(b: true, a: 1) }"#,
+        r#"def first(a: [int]) -> int { a[0] } def test() -> int { first([true]) }"#,
+        r#"def test(a: int) -> int { test(none) }"#,
     ];
 
     for (index, string) in strings.iter().enumerate() {
@@ -31,3 +82,186 @@ fn bad_typing() {
         }
     }
 }
+
+#[test]
+fn integer_literal_range() {
+    let strings = vec![
+        (r#"def test() -> int { 9223372036854775808 }"#, 21),
+        (r#"def test() -> int { -9223372036854775809 }"#, 22),
+        (r#"def test() -> int { 18446744073709551616 }"#, 21),
+        (r#"def test() -> float { 1e400 }"#, 23),
+        (r#"def test() -> float { -1.5e309 }"#, 24),
+    ];
+
+    for (index, (string, column)) in strings.iter().enumerate() {
+        match compile_str(string, &format!("string-{}", index)) {
+            Err(GearsError::ParseError { location, .. }) => {
+                assert_eq!((location.line(), location.column()), (1, *column), "{}", string)
+            }
+            other => panic!("Not a parse error {:?}: {:?}", string, other.map(|_| ())),
+        }
+    }
+}
+
+#[test]
+fn loop_control_outside_loop() {
+    let strings = vec![
+        (r#"def test() { break }"#, 14),
+        (r#"def test() { continue; }"#, 14),
+        (r#"def test() { if true { break; } }"#, 24),
+        (r#"def test() { for x in 0 to 2 {}; continue; }"#, 34),
+    ];
+
+    for (index, (string, column)) in strings.iter().enumerate() {
+        match compile_str(string, &format!("string-{}", index)) {
+            Err(GearsError::CompileError { location, .. }) => {
+                assert_eq!((location.line(), location.column()), (1, *column), "{}", string)
+            }
+            other => panic!("Not a compile error {:?}: {:?}", string, other.map(|_| ())),
+        }
+    }
+    match compile_str("def test() {\n    break;\n}", "message") {
+        Err(GearsError::CompileError { location, message }) => {
+            assert_eq!((location.line(), location.column()), (2, 5));
+            assert_eq!(message, "break is only allowed inside of a loop");
+        }
+        other => panic!("Expected a compile error, found: {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn return_outside_function() {
+    let strings = vec![
+        (r#"let a: int = if true { return 1; } else { 2 };"#, 24),
+        ("const b: int = 1;\nlet c: none = while true { return; };", 28),
+    ];
+
+    for (index, (string, column)) in strings.iter().enumerate() {
+        match compile_str(string, &format!("string-{}", index)) {
+            Err(GearsError::CompileError { location, .. }) => assert_eq!(
+                (location.line(), location.column()),
+                (string.lines().count(), *column),
+                "{}",
+                string
+            ),
+            other => panic!("Not a compile error {:?}: {:?}", string, other.map(|_| ())),
+        }
+    }
+
+    // A function literal in an initializer can return
+    compile_str(
+        "let f: () -> int = def() -> int { return 1; };",
+        "function literal",
+    )
+    .expect("Test failure");
+}
+
+#[test]
+fn unreachable_code() {
+    let strings = vec![
+        (r#"def test() -> int { return 1; 2 }"#, 1, 31),
+        (r#"def test() -> int { return 1; let a: int = 2; a }"#, 1, 31),
+        (r#"def test(a: bool) -> int { if a { return 1 } else { return 2 }; 3 }"#, 1, 65),
+        ("def test() {\n    while true {\n        break;\n        2\n    }\n}", 4, 9),
+    ];
+
+    for (index, (string, line, column)) in strings.iter().enumerate() {
+        match compile_str(string, &format!("string-{}", index)) {
+            Err(GearsError::CompileError { location, message }) => {
+                assert_eq!(
+                    message,
+                    "Unreachable code after a return, break or continue",
+                    "{}",
+                    string
+                );
+                assert_eq!(
+                    (location.line(), location.column()),
+                    (*line, *column),
+                    "{}",
+                    string
+                );
+            }
+            other => panic!("Not a compile error {:?}: {:?}", string, other.map(|_| ())),
+        }
+    }
+}
+
+#[test]
+fn unknown_types() {
+    let strings = vec![
+        r#"def test() -> banana { 1 }"#,
+        r#"def test(a: [int | banana]) {}"#,
+        r#"def test() { let a: {str: banana?} = ["a": none]; }"#,
+        r#"def test() { let f = def(a: banana) {}; }"#,
+        r#"struct Point { x: int, y: banana }"#,
+        r#"enum Shape { Circle(banana) }"#,
+        r#"let origin: banana = 0;"#,
+        r#"def test() -> list { [1] }"#,
+        r#"def banana() {} def test() -> banana {}"#,
+    ];
+
+    for (index, string) in strings.iter().enumerate() {
+        match compile_str(string, &format!("string-{}", index)) {
+            Ok(_) => panic!(format!("String passed. {:?}", string)),
+            Err(e) => match e {
+                GearsError::CompileError { .. } => {}
+                _ => {
+                    println!("{:?}", e);
+                    panic!("Not a compile error {:?}", string);
+                }
+            },
+        }
+    }
+
+    match compile_str("def test() {}\ndef other(a: int, b: banana) {}", "location") {
+        Err(GearsError::CompileError { location, message }) => {
+            assert_eq!((location.line(), location.column()), (2, 22));
+            assert_eq!(message, "Unknown type banana");
+        }
+        other => panic!("Expected a compile error, found: {:?}", other.map(|_| ())),
+    }
+
+    // User defined types can be used before they are declared
+    compile_str(
+        "struct Node { value: int, next: Node? }\ndef head(n: Node) -> Tree? { none }\nenum Tree { Leaf(Node) }",
+        "order",
+    )
+    .expect("Test failure");
+}
+
+#[test]
+fn call_errors_name_the_arg() {
+    let strings = vec![
+        (
+            r#"def add(a: int, b: int) -> int { a + b } def test() -> int { add(1, "2") }"#,
+            "add expects the arg b to be [Int], found: [Str]",
+        ),
+        (
+            r#"def add(a: int, b: int) -> int { a + b } def test() -> int { add(1) }"#,
+            "add is missing the arg b",
+        ),
+        (
+            r#"def add(a: int, b: int) -> int { a + b } def test() -> int { add(1, 2, 3) }"#,
+            "add takes 2 args, 3 were given",
+        ),
+        (
+            r#"def first<T>(items: [T]) -> T { items[0] } def test() -> int { first([1], [2]) }"#,
+            "first takes 1 args, 2 were given",
+        ),
+        (
+            r#"def add(a: int, b: int) -> int { a + b } def test() -> int { let f = add; f(1) }"#,
+            "The function takes 2 args, 1 were given",
+        ),
+        (
+            r#"def add(a: int, b: int) -> int { a + b } def test() -> int { let f = add; f(1, 2, 3) }"#,
+            "The function takes 2 args, 3 were given",
+        ),
+    ];
+
+    for (string, message) in strings {
+        assert_eq!(
+            compile_str(string, "call_errors").unwrap_err(),
+            GearsError::TypeError(message.to_string())
+        );
+    }
+}